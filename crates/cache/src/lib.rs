@@ -42,10 +42,12 @@ where
     Middleware::new(move |ctx: TCtx, input: TInput, next| {
         async move {
             let meta = next.meta();
-            let cache = meta.state().get::<CacheState>().unwrap(); // TODO: Error handling
+            let cache = meta
+                .state()
+                .get::<CacheState>()
+                .expect("unreachable: `CacheState` is required by `Router::build`");
 
-            let key = "todo"; // TODO: Work this out properly
-                              // TODO: Keyed to `TInput`
+            let key = meta.name(); // TODO: Keyed to `TInput`
 
             if let Some(value) = cache.store().get(key) {
                 let value: &TResult = value.downcast_ref().unwrap(); // TODO: Error
@@ -68,4 +70,5 @@ where
             result
         }
     })
+    .setup(|state, _| state.require::<CacheState>())
 }
@@ -83,6 +83,7 @@ impl<P> Client<P> {
                 let res = self
                     .client
                     .post(&url)
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
                     .body(serde_json::to_string(&input).unwrap()) // TODO: Error handling
                     .send()
                     .await
@@ -112,6 +113,13 @@ impl<P> Client<P> {
     }
 }
 
+/// Used by the bindings generated by `rspc::Rust` so they don't depend on the user's `serde` version.
+#[doc(hidden)]
+pub mod internal {
+    pub use serde;
+    pub use serde_json;
+}
+
 pub trait Procedure {
     type Input: Serialize;
     type Output: DeserializeOwned;
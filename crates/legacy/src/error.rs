@@ -148,6 +148,11 @@ impl Error {
         &self.message
     }
 
+    #[doc(hidden)]
+    pub fn code(&self) -> &ErrorCode {
+        &self.code
+    }
+
     #[doc(hidden)]
     pub fn cause(self) -> Option<Arc<dyn error::Error + Send + Sync>> {
         self.cause
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, panic::Location};
 
 use specta::DataType;
 
@@ -15,6 +15,8 @@ pub struct ProcedureDataType {
 pub struct Procedure<TCtx> {
     pub exec: Box<dyn Layer<TCtx>>,
     pub ty: ProcedureDataType,
+    /// Where the procedure was defined. This is used for the Typescript source maps.
+    pub location: Location<'static>,
 }
 
 pub struct ProcedureStore<TCtx> {
@@ -30,7 +32,13 @@ impl<TCtx> ProcedureStore<TCtx> {
         }
     }
 
-    pub fn append(&mut self, key: String, exec: Box<dyn Layer<TCtx>>, ty: ProcedureDataType) {
+    pub fn append(
+        &mut self,
+        key: String,
+        exec: Box<dyn Layer<TCtx>>,
+        ty: ProcedureDataType,
+        location: Location<'static>,
+    ) {
         #[allow(clippy::panic)]
         if key.is_empty() || key == "ws" || key.starts_with("rpc.") || key.starts_with("rspc.") {
             panic!(
@@ -48,6 +56,6 @@ impl<TCtx> ProcedureStore<TCtx> {
             );
         }
 
-        self.store.insert(key, Procedure { exec, ty });
+        self.store.insert(key, Procedure { exec, ty, location });
     }
 }
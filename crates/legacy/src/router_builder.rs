@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{marker::PhantomData, panic::Location};
 
 use futures::Stream;
 use serde::{de::DeserializeOwned, Serialize};
@@ -112,6 +112,7 @@ where
         }
     }
 
+    #[track_caller]
     pub fn query<TResolver, TArg, TResult, TResultMarker>(
         mut self,
         key: &'static str,
@@ -137,10 +138,12 @@ where
                 phantom: PhantomData,
             }),
             TResolver::typedef(&mut self.type_map),
+            *Location::caller(),
         );
         self
     }
 
+    #[track_caller]
     pub fn mutation<TResolver, TArg, TResult, TResultMarker>(
         mut self,
         key: &'static str,
@@ -166,10 +169,12 @@ where
                 phantom: PhantomData,
             }),
             TResolver::typedef(&mut self.type_map),
+            *Location::caller(),
         );
         self
     }
 
+    #[track_caller]
     pub fn subscription<TResolver, TArg, TStream, TResult, TResultMarker>(
         mut self,
         key: &'static str,
@@ -200,6 +205,7 @@ where
                 phantom: PhantomData,
             }),
             TResolver::typedef(&mut self.type_map),
+            *Location::caller(),
         );
         self
     }
@@ -230,6 +236,7 @@ where
                 format!("{}{}", prefix, key),
                 self.middleware.build(query.exec),
                 query.ty,
+                query.location,
             );
         }
 
@@ -239,6 +246,7 @@ where
                 format!("{}{}", prefix, key),
                 self.middleware.build(mutation.exec),
                 mutation.ty,
+                mutation.location,
             );
         }
 
@@ -248,6 +256,7 @@ where
                 format!("{}{}", prefix, key),
                 self.middleware.build(subscription.exec),
                 subscription.ty,
+                subscription.location,
             );
         }
 
@@ -298,6 +307,7 @@ where
                 format!("{}{}", prefix, key),
                 middleware.build(query.exec),
                 query.ty,
+                query.location,
             );
         }
 
@@ -306,6 +316,7 @@ where
                 format!("{}{}", prefix, key),
                 middleware.build(mutation.exec),
                 mutation.ty,
+                mutation.location,
             );
         }
 
@@ -314,6 +325,7 @@ where
                 format!("{}{}", prefix, key),
                 middleware.build(subscription.exec),
                 subscription.ty,
+                subscription.location,
             );
         }
 
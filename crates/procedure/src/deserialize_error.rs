@@ -0,0 +1,205 @@
+use std::{error, fmt};
+
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+/// An error which occurred while deserializing the input of a procedure.
+///
+/// This is sent to the client with the path to the field which failed so it can be shown next to the field (Eg. in a form).
+pub struct DeserializeError {
+    path: Vec<PathSegment>,
+    message: String,
+    expected: Option<String>,
+    found: Option<String>,
+}
+
+impl DeserializeError {
+    pub fn custom<T: fmt::Display>(err: T) -> Self {
+        Self::new(Vec::new(), err.to_string())
+    }
+
+    pub(crate) fn from_path_error(err: serde_path_to_error::Error<erased_serde::Error>) -> Self {
+        let path = err
+            .path()
+            .iter()
+            .filter_map(|segment| match segment {
+                serde_path_to_error::Segment::Seq { index } => Some(PathSegment::Index(*index)),
+                serde_path_to_error::Segment::Map { key }
+                | serde_path_to_error::Segment::Enum { variant: key } => {
+                    Some(PathSegment::Field(key.clone()))
+                }
+                serde_path_to_error::Segment::Unknown => None,
+            })
+            .collect();
+
+        Self::new(path, err.into_inner().to_string())
+    }
+
+    fn new(mut path: Vec<PathSegment>, message: String) -> Self {
+        let (expected, found) = parse_message(&message);
+
+        // The path points to the struct so we add the field so it can be highlighted.
+        if let Some(field) = message
+            .strip_prefix("missing field `")
+            .and_then(|v| v.split_once('`'))
+            .map(|(field, _)| field)
+        {
+            path.push(PathSegment::Field(field.into()));
+        }
+
+        Self {
+            path,
+            message,
+            expected,
+            found,
+        }
+    }
+
+    /// The path to the value which failed to deserialize. This is empty if the error was for the whole input.
+    pub fn path(&self) -> &[PathSegment] {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// A description of the value which was expected. Eg. `u32` or `a string`.
+    pub fn expected(&self) -> Option<&str> {
+        self.expected.as_deref()
+    }
+
+    /// A description of the value which was provided. Eg. `string "abc"`.
+    pub fn found(&self) -> Option<&str> {
+        self.found.as_deref()
+    }
+
+    pub(crate) fn serialize_fields<S: SerializeStruct>(
+        &self,
+        state: &mut S,
+    ) -> Result<(), S::Error> {
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field("expected", &self.expected)?;
+        state.serialize_field("found", &self.found)
+    }
+}
+
+impl fmt::Debug for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeserializeError")
+            .field("path", &self.path)
+            .field("message", &self.message)
+            .field("expected", &self.expected)
+            .field("found", &self.found)
+            .finish()
+    }
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.path.iter().enumerate() {
+            if i != 0 && matches!(segment, PathSegment::Field(_)) {
+                f.write_str(".")?;
+            }
+            write!(f, "{segment}")?;
+        }
+        if !self.path.is_empty() {
+            f.write_str(": ")?;
+        }
+
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for DeserializeError {}
+
+impl Serialize for DeserializeError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DeserializeError", 4)?;
+        state.serialize_field("message", &self.message)?;
+        self.serialize_fields(&mut state)?;
+        state.end()
+    }
+}
+
+/// A single segment of [`DeserializeError::path`].
+///
+/// This is serialized as a string or number so the path is a valid JSON path (Eg. `["users", 0, "name"]`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// The field of a struct, key of a map or variant of an enum.
+    Field(String),
+    /// The index of an item in a list.
+    Index(usize),
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(field) => f.write_str(field),
+            Self::Index(index) => write!(f, "[{index}]"),
+        }
+    }
+}
+
+impl Serialize for PathSegment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Field(field) => serializer.serialize_str(field),
+            Self::Index(index) => serializer.serialize_u64(*index as u64),
+        }
+    }
+}
+
+// `erased_serde` only gives us the message of the error so we parse the format used by the default methods on `serde::de::Error`.
+// If the deserializer uses a different format this returns `None` and the client will only have the message.
+fn parse_message(message: &str) -> (Option<String>, Option<String>) {
+    // Eg. `serde_json` includes the position of the error when deserializing from a string.
+    let message = match message.rsplit_once(" at line ") {
+        Some((message, position))
+            if position
+                .split_once(" column ")
+                .is_some_and(|(l, c)| is_number(l) && is_number(c)) =>
+        {
+            message
+        }
+        _ => message,
+    };
+
+    let (found, expected) = if let Some(rest) = message
+        .strip_prefix("invalid type: ")
+        .or_else(|| message.strip_prefix("invalid value: "))
+    {
+        match rest.rsplit_once(", expected ") {
+            Some((found, expected)) => (Some(found.to_string()), Some(expected.to_string())),
+            None => (None, None),
+        }
+    } else if let Some(rest) = message.strip_prefix("invalid length ") {
+        match rest.split_once(", expected ") {
+            Some((length, expected)) => {
+                (Some(format!("length {length}")), Some(expected.to_string()))
+            }
+            None => (None, None),
+        }
+    } else if let Some(rest) = message
+        .strip_prefix("unknown field ")
+        .or_else(|| message.strip_prefix("unknown variant "))
+    {
+        match rest.split_once(", expected ") {
+            Some((found, expected)) => (Some(found.to_string()), Some(expected.to_string())),
+            None => (
+                rest.split_once(", ").map(|(found, _)| found.to_string()),
+                None,
+            ),
+        }
+    } else if let Some(field) = message.strip_prefix("missing field ") {
+        (None, Some(format!("field {field}")))
+    } else {
+        (None, None)
+    };
+
+    (expected, found)
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
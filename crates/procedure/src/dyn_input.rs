@@ -3,7 +3,7 @@ use std::{
     fmt,
 };
 
-use serde::{de::Error, Deserialize};
+use serde::Deserialize;
 
 use crate::{DeserializeError, DowncastError, ProcedureError};
 
@@ -42,16 +42,16 @@ impl<'a, 'de> DynInput<'a, 'de> {
     /// TODO
     pub fn deserialize<T: Deserialize<'de>>(self) -> Result<T, ProcedureError> {
         let Repr::Deserializer(deserializer) = self.inner else {
-            return Err(ProcedureError::Deserialize(DeserializeError(
-                erased_serde::Error::custom(format!(
+            return Err(ProcedureError::Deserialize(DeserializeError::custom(
+                format!(
                     "attempted to deserialize from value '{}' but expected deserializer",
                     self.type_name
-                )),
+                ),
             )));
         };
 
-        erased_serde::deserialize(deserializer)
-            .map_err(|err| ProcedureError::Deserialize(DeserializeError(err)))
+        serde_path_to_error::deserialize(deserializer)
+            .map_err(|err| ProcedureError::Deserialize(DeserializeError::from_path_error(err)))
     }
 
     /// TODO
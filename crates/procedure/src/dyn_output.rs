@@ -1,11 +1,12 @@
 use std::{
     any::{type_name, Any},
     fmt,
+    panic::{catch_unwind, AssertUnwindSafe},
 };
 
-use serde::Serialize;
+use serde::{Serialize, Serializer};
 
-use crate::ProcedureError;
+use crate::{ProcedureError, SerializerError};
 
 /// TODO
 pub struct DynOutput<'a> {
@@ -16,6 +17,30 @@ pub struct DynOutput<'a> {
 enum Repr<'a> {
     Serialize(&'a (dyn erased_serde::Serialize + Send + Sync)),
     Value(&'a mut (dyn Any + Send)),
+    // A serializable value which can also be taken with `as_value`.
+    SerializeValue(&'a mut dyn SerializeValue),
+}
+
+// Implemented for the `Option<Result<T, ProcedureError>>` held by `ProcedureStream`.
+trait SerializeValue: Send {
+    fn as_serialize(&self) -> &(dyn erased_serde::Serialize + Send + Sync);
+
+    fn as_any(&mut self) -> &mut (dyn Any + Send);
+}
+
+impl<T: Serialize + Send + Sync + 'static> SerializeValue for Option<Result<T, ProcedureError>> {
+    fn as_serialize(&self) -> &(dyn erased_serde::Serialize + Send + Sync) {
+        match self {
+            Some(Ok(v)) => v,
+            // Error's are caught before `as_value` is called and
+            // attempted to access value when `Poll::Ready(None)` was not returned.
+            _ => unreachable!(),
+        }
+    }
+
+    fn as_any(&mut self) -> &mut (dyn Any + Send) {
+        self
+    }
 }
 
 // TODO: `Debug`, etc traits
@@ -29,6 +54,16 @@ impl<'a> DynOutput<'a> {
         }
     }
 
+    // Used by `ProcedureStream` so in-process callers can take the value without serializing it.
+    pub(crate) fn new_serialize_value<T: Serialize + Send + Sync + 'static>(
+        value: &'a mut Option<Result<T, ProcedureError>>,
+    ) -> Self {
+        Self {
+            inner: Repr::SerializeValue(value),
+            type_name: type_name::<T>(),
+        }
+    }
+
     pub fn new_serialize<T: Serialize + Send + Sync>(value: &'a mut T) -> Self {
         Self {
             inner: Repr::Serialize(value),
@@ -36,24 +71,52 @@ impl<'a> DynOutput<'a> {
         }
     }
 
+    /// The name of the type being returned. This is useful for error messages.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
     /// TODO
     pub fn as_serialize(self) -> Option<impl Serialize + Send + Sync + 'a> {
         match self.inner {
             Repr::Serialize(v) => Some(v),
             Repr::Value(_) => None,
+            Repr::SerializeValue(v) => {
+                let v: &'a dyn SerializeValue = v;
+                Some(v.as_serialize())
+            }
         }
     }
 
-    /// TODO
+    /// Serialize the value with `serializer`.
+    ///
+    /// Unlike [`DynOutput::as_serialize`] this returns a [`ProcedureError::Serializer`] if the value isn't serializable or it's `Serialize` implementation fails
+    /// and a [`ProcedureError::Unwind`] if it's `Serialize` implementation panics.
+    pub fn serialize_with<S: Serializer>(self, serializer: S) -> Result<S::Ok, ProcedureError> {
+        let type_name = self.type_name;
+        let value = self
+            .as_serialize()
+            .ok_or_else(|| SerializerError::custom(format!("'{type_name}' is not serializable")))?;
+
+        catch_unwind(AssertUnwindSafe(|| value.serialize(serializer)))
+            .map_err(ProcedureError::Unwind)?
+            .map_err(|err| SerializerError::custom(err).into())
+    }
+
+    /// Take the value if it is of type `T`.
+    ///
+    /// This works for values returned with `ProcedureStream::from_*_value` and for serializable values, allowing them to be used in-process without a serialization round-trip.
     pub fn as_value<T: Send + 'static>(self) -> Option<T> {
-        match self.inner {
-            Repr::Serialize(_) => None,
-            Repr::Value(v) => v
-                .downcast_mut::<Option<Result<T, ProcedureError>>>()?
-                .take()
-                .expect("unreachable")
-                .ok(),
-        }
+        let v = match self.inner {
+            Repr::Serialize(_) => return None,
+            Repr::Value(v) => v,
+            Repr::SerializeValue(v) => v.as_any(),
+        };
+
+        v.downcast_mut::<Option<Result<T, ProcedureError>>>()?
+            .take()
+            .expect("unreachable")
+            .ok()
     }
 }
 
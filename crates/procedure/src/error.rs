@@ -1,10 +1,13 @@
-use std::{any::Any, borrow::Cow, error, fmt};
-
-use serde::{
-    ser::{Error, SerializeStruct},
-    Serialize, Serializer,
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    error, fmt,
 };
 
+use serde::{ser::SerializeStruct, Serialize, Serializer};
+
+use crate::{DeserializeError, ErrorCode};
+
 // TODO: Discuss the stability guanrantees of the error handling system. Variant is fixed, message is not.
 
 /// TODO
@@ -19,10 +22,12 @@ pub enum ProcedureError {
     Resolver(ResolverError),
     /// The procedure unexpectedly unwinded.
     /// This happens when you panic inside a procedure.
+    ///
+    /// Panics are caught every time the [`ProcedureStream`](crate::ProcedureStream) is polled and it will end after yielding this error.
     Unwind(Box<dyn Any + Send>),
-    // /// An error occurred while serializing the response.
-    // /// The error message can be provided should be omitted unless the client is trusted (Eg. Tauri).
-    // Serializer(Option<String>), // TODO: Sort this out
+    /// An error occurred while serializing the response.
+    /// The error message is omitted when sent to the client as it could leak internal details.
+    Serializer(SerializerError),
 }
 
 impl ProcedureError {
@@ -33,7 +38,19 @@ impl ProcedureError {
             ProcedureError::Downcast(_) => "Downcast",
             ProcedureError::Resolver(_) => "Resolver",
             ProcedureError::Unwind(_) => "ResolverPanic",
-            // ProcedureError::Serializer(_) => "Serializer",
+            ProcedureError::Serializer(_) => "Serializer",
+        }
+    }
+
+    /// The [`ErrorCode`] integrations should use when returning this error to the client.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ProcedureError::NotFound => ErrorCode::NotFound,
+            ProcedureError::Deserialize(_) => ErrorCode::BadRequest,
+            ProcedureError::Downcast(_) => ErrorCode::BadRequest,
+            ProcedureError::Resolver(err) => err.code(),
+            ProcedureError::Unwind(_) => ErrorCode::InternalServerError,
+            ProcedureError::Serializer(_) => ErrorCode::InternalServerError,
         }
     }
 
@@ -41,17 +58,15 @@ impl ProcedureError {
     pub fn message(&self) -> Cow<'static, str> {
         match self {
             ProcedureError::NotFound => "procedure not found".into(),
-            ProcedureError::Deserialize(err) => err.0.to_string().into(),
+            ProcedureError::Deserialize(err) => err.to_string().into(),
             ProcedureError::Downcast(err) => err.to_string().into(),
             ProcedureError::Resolver(err) => err
                 .error()
                 .map(|err| err.to_string().into())
                 .unwrap_or("resolver error".into()),
             ProcedureError::Unwind(_) => "resolver panic".into(),
-            // ProcedureError::Serializer(err) => err
-            //     .clone()
-            //     .map(Into::into)
-            //     .unwrap_or("serializer error".into()),
+            // The message is only available via `SerializerError::message` so it's not sent to the client.
+            ProcedureError::Serializer(_) => "serializer error".into(),
         }
     }
 }
@@ -68,6 +83,12 @@ impl From<DeserializeError> for ProcedureError {
     }
 }
 
+impl From<SerializerError> for ProcedureError {
+    fn from(err: SerializerError) -> Self {
+        ProcedureError::Serializer(err)
+    }
+}
+
 impl From<DowncastError> for ProcedureError {
     fn from(err: DowncastError) -> Self {
         ProcedureError::Downcast(err)
@@ -83,7 +104,7 @@ impl fmt::Debug for ProcedureError {
             Self::Downcast(err) => write!(f, "Downcast({err:?})"),
             Self::Resolver(err) => write!(f, "Resolver({err:?})"),
             Self::Unwind(err) => write!(f, "ResolverPanic({err:?})"),
-            // Self::Serializer(err) => write!(f, "Serializer({err:?})"),
+            Self::Serializer(err) => write!(f, "Serializer({err:?})"),
         }
     }
 }
@@ -105,40 +126,88 @@ impl Serialize for ProcedureError {
             return err.value().serialize(serializer);
         }
 
-        let mut state = serializer.serialize_struct("ProcedureError", 3)?;
+        let len = if matches!(self, ProcedureError::Deserialize(_)) {
+            6
+        } else {
+            3
+        };
+        let mut state = serializer.serialize_struct("ProcedureError", len)?;
         state.serialize_field("~rspc", &true)?;
         state.serialize_field("variant", &self.variant())?;
         state.serialize_field("message", &self.message())?;
+        // This allows the client to show the error next to the field which caused it.
+        if let ProcedureError::Deserialize(err) = self {
+            err.serialize_fields(&mut state)?;
+        }
         state.end()
     }
 }
 
 /// TODO
-pub struct ResolverError(Box<dyn ErrorInternalExt>);
+pub struct ResolverError {
+    inner: Box<dyn ErrorInternalExt>,
+    code: ErrorCode,
+}
 
 impl ResolverError {
-    // Warning: Returning > 400 will fallback to `500`. As redirects would be invalid and `200` would break matching.
+    /// Construct a new error with the value which will be sent to the client.
+    ///
+    /// This defaults to [`ErrorCode::InternalServerError`]. Use [`ResolverError::with_code`] to change it.
     pub fn new<T: Serialize + Send + 'static, E: error::Error + Send + 'static>(
         value: T,
         source: Option<E>,
     ) -> Self {
-        Self(Box::new(ErrorInternal { value, err: source }))
+        Self {
+            inner: Box::new(ErrorInternal { value, err: source }),
+            code: ErrorCode::InternalServerError,
+        }
+    }
+
+    /// Set the [`ErrorCode`] integrations will use when returning this error (Eg. as the HTTP status).
+    pub fn with_code(mut self, code: ErrorCode) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// TODO
+    pub fn code(&self) -> ErrorCode {
+        self.code
     }
 
     /// TODO
     pub fn value(&self) -> impl Serialize + '_ {
-        self.0.value()
+        self.inner.value()
     }
 
     /// TODO
     pub fn error(&self) -> Option<&(dyn error::Error + Send + 'static)> {
-        self.0.error()
+        self.inner.error()
+    }
+
+    /// Take the value of the error if it is of type `T`.
+    ///
+    /// This allows in-process callers to get back the typed error returned by a procedure.
+    pub fn downcast<T: 'static>(self) -> Result<T, Self> {
+        if self.inner.value_type_id() != TypeId::of::<T>() {
+            return Err(self);
+        }
+
+        Ok(*self
+            .inner
+            .into_value()
+            .downcast::<T>()
+            .expect("unreachable: type was checked above"))
     }
 }
 
 impl fmt::Debug for ResolverError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "ResolverError({:?})", self.0.debug())
+        write!(
+            f,
+            "ResolverError({:?}, {:?})",
+            self.code,
+            self.inner.debug()
+        )
     }
 }
 
@@ -150,28 +219,35 @@ impl fmt::Display for ResolverError {
 
 impl error::Error for ResolverError {}
 
-/// TODO
-pub struct DeserializeError(pub(crate) erased_serde::Error);
+/// An error which occurred while serializing the result of a procedure.
+pub struct SerializerError(String);
 
-impl DeserializeError {
+impl SerializerError {
     pub fn custom<T: fmt::Display>(err: T) -> Self {
-        Self(erased_serde::Error::custom(err))
+        Self(err.to_string())
+    }
+
+    /// The unredacted error message.
+    ///
+    /// This could contain internal details so it should only be shown to trusted clients or logged.
+    pub fn message(&self) -> &str {
+        &self.0
     }
 }
 
-impl fmt::Debug for DeserializeError {
+impl fmt::Debug for SerializerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Deserialize({:?})", self.0)
+        write!(f, "Serializer({:?})", self.0)
     }
 }
 
-impl fmt::Display for DeserializeError {
+impl fmt::Display for SerializerError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{self:?}")
     }
 }
 
-impl error::Error for DeserializeError {}
+impl error::Error for SerializerError {}
 
 /// TODO
 pub struct DowncastError {
@@ -182,6 +258,13 @@ pub struct DowncastError {
     pub(crate) to: &'static str,
 }
 
+impl DowncastError {
+    /// Construct a new error for when a value of type `from` was provided but a `to` was expected.
+    pub fn new(from: Option<&'static str>, to: &'static str) -> Self {
+        Self { from, to }
+    }
+}
+
 impl fmt::Debug for DowncastError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Downcast(from: {:?}, to: {:?})", self.from, self.to)
@@ -207,6 +290,10 @@ trait ErrorInternalExt: Send {
     fn error(&self) -> Option<&(dyn error::Error + Send + 'static)>;
 
     fn debug(&self) -> Option<&dyn fmt::Debug>;
+
+    fn value_type_id(&self) -> TypeId;
+
+    fn into_value(self: Box<Self>) -> Box<dyn Any>;
 }
 
 impl<T: Serialize + Send + 'static, E: error::Error + Send + 'static> ErrorInternalExt
@@ -225,4 +312,12 @@ impl<T: Serialize + Send + 'static, E: error::Error + Send + 'static> ErrorInter
     fn debug(&self) -> Option<&dyn fmt::Debug> {
         self.err.as_ref().map(|err| err as &dyn fmt::Debug)
     }
+
+    fn value_type_id(&self) -> TypeId {
+        TypeId::of::<T>()
+    }
+
+    fn into_value(self: Box<Self>) -> Box<dyn Any> {
+        Box::new(self.value)
+    }
 }
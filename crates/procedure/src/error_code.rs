@@ -0,0 +1,171 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+
+/// A standardized code describing the cause of an error.
+///
+/// This is used by every integration to determine the HTTP status or JSON-RPC error code to return.
+/// The codes match what [tRPC](https://trpc.io/docs/server/error-handling#error-codes) uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    BadRequest,
+    Unauthorized,
+    PaymentRequired,
+    Forbidden,
+    NotFound,
+    MethodNotSupported,
+    Timeout,
+    Conflict,
+    PreconditionFailed,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    UnprocessableContent,
+    TooManyRequests,
+    ClientClosedRequest,
+    InternalServerError,
+    NotImplemented,
+    BadGateway,
+    ServiceUnavailable,
+    GatewayTimeout,
+}
+
+impl ErrorCode {
+    /// Every error code.
+    pub const ALL: [Self; 19] = [
+        Self::BadRequest,
+        Self::Unauthorized,
+        Self::PaymentRequired,
+        Self::Forbidden,
+        Self::NotFound,
+        Self::MethodNotSupported,
+        Self::Timeout,
+        Self::Conflict,
+        Self::PreconditionFailed,
+        Self::PayloadTooLarge,
+        Self::UnsupportedMediaType,
+        Self::UnprocessableContent,
+        Self::TooManyRequests,
+        Self::ClientClosedRequest,
+        Self::InternalServerError,
+        Self::NotImplemented,
+        Self::BadGateway,
+        Self::ServiceUnavailable,
+        Self::GatewayTimeout,
+    ];
+
+    /// The HTTP status code for this error.
+    pub const fn status_code(&self) -> u16 {
+        match self {
+            Self::BadRequest => 400,
+            Self::Unauthorized => 401,
+            Self::PaymentRequired => 402,
+            Self::Forbidden => 403,
+            Self::NotFound => 404,
+            Self::MethodNotSupported => 405,
+            Self::Timeout => 408,
+            Self::Conflict => 409,
+            Self::PreconditionFailed => 412,
+            Self::PayloadTooLarge => 413,
+            Self::UnsupportedMediaType => 415,
+            Self::UnprocessableContent => 422,
+            Self::TooManyRequests => 429,
+            Self::ClientClosedRequest => 499,
+            Self::InternalServerError => 500,
+            Self::NotImplemented => 501,
+            Self::BadGateway => 502,
+            Self::ServiceUnavailable => 503,
+            Self::GatewayTimeout => 504,
+        }
+    }
+
+    /// Get the error code for an HTTP status code.
+    ///
+    /// Returns `None` if the status code isn't an error we have a code for.
+    pub const fn from_status_code(status_code: u16) -> Option<Self> {
+        Some(match status_code {
+            400 => Self::BadRequest,
+            401 => Self::Unauthorized,
+            402 => Self::PaymentRequired,
+            403 => Self::Forbidden,
+            404 => Self::NotFound,
+            405 => Self::MethodNotSupported,
+            408 => Self::Timeout,
+            409 => Self::Conflict,
+            412 => Self::PreconditionFailed,
+            413 => Self::PayloadTooLarge,
+            415 => Self::UnsupportedMediaType,
+            422 => Self::UnprocessableContent,
+            429 => Self::TooManyRequests,
+            499 => Self::ClientClosedRequest,
+            500 => Self::InternalServerError,
+            501 => Self::NotImplemented,
+            502 => Self::BadGateway,
+            503 => Self::ServiceUnavailable,
+            504 => Self::GatewayTimeout,
+            _ => return None,
+        })
+    }
+
+    /// The [JSON-RPC 2.0](https://www.jsonrpc.org/specification#error_object) error code for this error.
+    pub const fn json_rpc_code(&self) -> i32 {
+        match self {
+            Self::BadRequest => -32600,
+            Self::Unauthorized => -32001,
+            Self::PaymentRequired => -32002,
+            Self::Forbidden => -32003,
+            Self::NotFound => -32004,
+            Self::MethodNotSupported => -32005,
+            Self::Timeout => -32008,
+            Self::Conflict => -32009,
+            Self::PreconditionFailed => -32012,
+            Self::PayloadTooLarge => -32013,
+            Self::UnsupportedMediaType => -32015,
+            Self::UnprocessableContent => -32022,
+            Self::TooManyRequests => -32029,
+            Self::ClientClosedRequest => -32099,
+            Self::InternalServerError
+            | Self::NotImplemented
+            | Self::BadGateway
+            | Self::ServiceUnavailable
+            | Self::GatewayTimeout => -32603,
+        }
+    }
+
+    /// The name of the code. Eg. `NOT_FOUND`.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::BadRequest => "BAD_REQUEST",
+            Self::Unauthorized => "UNAUTHORIZED",
+            Self::PaymentRequired => "PAYMENT_REQUIRED",
+            Self::Forbidden => "FORBIDDEN",
+            Self::NotFound => "NOT_FOUND",
+            Self::MethodNotSupported => "METHOD_NOT_SUPPORTED",
+            Self::Timeout => "TIMEOUT",
+            Self::Conflict => "CONFLICT",
+            Self::PreconditionFailed => "PRECONDITION_FAILED",
+            Self::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            Self::UnsupportedMediaType => "UNSUPPORTED_MEDIA_TYPE",
+            Self::UnprocessableContent => "UNPROCESSABLE_CONTENT",
+            Self::TooManyRequests => "TOO_MANY_REQUESTS",
+            Self::ClientClosedRequest => "CLIENT_CLOSED_REQUEST",
+            Self::InternalServerError => "INTERNAL_SERVER_ERROR",
+            Self::NotImplemented => "NOT_IMPLEMENTED",
+            Self::BadGateway => "BAD_GATEWAY",
+            Self::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            Self::GatewayTimeout => "GATEWAY_TIMEOUT",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
@@ -0,0 +1,20 @@
+use std::fmt;
+
+// TODO: This is a clone of `rspc::ProcedureKind`. I don't like us having both but this crate doesn't depend on Specta.
+/// The kind of operation a [`Procedure`](crate::Procedure) performs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ProcedureKind {
+    Query,
+    Mutation,
+    Subscription,
+}
+
+impl fmt::Display for ProcedureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query => write!(f, "Query"),
+            Self::Mutation => write!(f, "Mutation"),
+            Self::Subscription => write!(f, "Subscription"),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{DynInput, Procedure, ProcedureStream};
+
+/// A type-erased interceptor which wraps the execution of a [`Procedure`].
+///
+/// Layers are applied to every procedure with [`Procedures::layer`](crate::Procedures::layer).
+/// They are given the name of the procedure and the [`Procedure`] itself, from which the [`ProcedureKind`](crate::ProcedureKind) can be read.
+///
+/// To continue execution the layer should call [`Procedure::exec`] and return (or wrap) the resulting [`ProcedureStream`].
+/// To short-circuit the layer can return a [`ProcedureError`](crate::ProcedureError) by converting it into a [`ProcedureStream`].
+///
+/// This is implemented for all functions with a matching signature.
+pub trait ProcedureLayer<TCtx>: Send + Sync + 'static {
+    fn call(
+        &self,
+        name: &str,
+        procedure: &Procedure<TCtx>,
+        ctx: TCtx,
+        input: DynInput,
+    ) -> ProcedureStream;
+}
+
+impl<TCtx, F> ProcedureLayer<TCtx> for F
+where
+    F: Fn(&str, &Procedure<TCtx>, TCtx, DynInput) -> ProcedureStream + Send + Sync + 'static,
+{
+    fn call(
+        &self,
+        name: &str,
+        procedure: &Procedure<TCtx>,
+        ctx: TCtx,
+        input: DynInput,
+    ) -> ProcedureStream {
+        (self)(name, procedure, ctx, input)
+    }
+}
@@ -16,22 +16,31 @@
     html_favicon_url = "https://github.com/specta-rs/rspc/raw/main/.github/logo.png"
 )]
 
+mod deserialize_error;
 mod dyn_input;
 mod dyn_output;
 mod error;
+mod error_code;
 mod interop;
+mod kind;
+mod layer;
 mod logger;
 mod procedure;
 mod procedures;
 mod state;
 mod stream;
 
+pub use deserialize_error::{DeserializeError, PathSegment};
 pub use dyn_input::DynInput;
 pub use dyn_output::DynOutput;
-pub use error::{DeserializeError, DowncastError, ProcedureError, ResolverError};
+pub use error::{DowncastError, ProcedureError, ResolverError, SerializerError};
+pub use error_code::ErrorCode;
 #[doc(hidden)]
 pub use interop::LegacyErrorInterop;
+pub use kind::ProcedureKind;
+pub use layer::ProcedureLayer;
+pub use logger::{LogMessage, Logger};
 pub use procedure::Procedure;
 pub use procedures::Procedures;
 pub use state::State;
-pub use stream::{flush, ProcedureStream, ProcedureStreamMap};
+pub use stream::{closing, flush, ProcedureStream, ProcedureStreamMap};
@@ -1,9 +1,71 @@
-// #[derive(Clone, Debug)]
-// #[non_exhaustive]
-// pub enum LogMessage<'a> {
-//     Execute { name: &'a str }, // TODO: Give enough information to collect time metrics, etc.
-//     SerializerError(&'a str),
-//     Custom { reason: &'a str, message: &'a str },
-// }
-
-// TODO: Clone `ProcedureError` instead of `Event`?
+use std::{any::Any, fmt, sync::Arc};
+
+use crate::SerializerError;
+
+type LogFn = dyn Fn(LogMessage<'_>) + Send + Sync;
+
+/// An event which is reported to the [`Logger`].
+///
+/// These are errors which can't be reported to the client (or only in a redacted form) so they should be logged instead.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum LogMessage<'a> {
+    /// Serializing the result of a procedure failed.
+    Serializer {
+        procedure: &'a str,
+        error: &'a SerializerError,
+    },
+    /// A procedure panicked while it was being executed or polled.
+    ///
+    /// The panic is caught and sent to the client as a [`ProcedureError::Unwind`](crate::ProcedureError::Unwind) so this is the only place the payload is available.
+    Panic {
+        procedure: &'a str,
+        payload: &'a (dyn Any + Send),
+    },
+}
+
+impl fmt::Display for LogMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Serializer { procedure, error } => write!(
+                f,
+                "error serializing result of procedure '{procedure}': {}",
+                error.message()
+            ),
+            Self::Panic { procedure, payload } => {
+                // `panic!` produces a `&str` or `String` payload. Anything else came from `std::panic::panic_any`.
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("Box<dyn Any>");
+                write!(f, "procedure '{procedure}' panicked: {message}")
+            }
+        }
+    }
+}
+
+/// A function for logging [`LogMessage`]s. Refer to [`Procedures::with_logger`](crate::Procedures::with_logger).
+///
+/// This is cheap to clone so integrations can hold onto it.
+#[derive(Clone, Default)]
+pub struct Logger(Option<Arc<LogFn>>);
+
+impl Logger {
+    pub fn new(func: impl Fn(LogMessage<'_>) + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(func)))
+    }
+
+    /// Report a message. This does nothing if no logger has been registered.
+    pub fn log(&self, message: LogMessage<'_>) {
+        if let Some(func) = &self.0 {
+            func(message);
+        }
+    }
+}
+
+impl fmt::Debug for Logger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Logger").field(&self.0.is_some()).finish()
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    any::type_name,
+    any::{type_name, Any},
     fmt,
     panic::{catch_unwind, AssertUnwindSafe},
     sync::Arc,
@@ -7,7 +7,7 @@ use std::{
 
 use serde::Deserializer;
 
-use crate::{DynInput, ProcedureError, ProcedureStream};
+use crate::{DynInput, ProcedureError, ProcedureKind, ProcedureStream};
 
 // TODO: Discuss cancellation safety
 
@@ -15,6 +15,8 @@ use crate::{DynInput, ProcedureError, ProcedureStream};
 ///
 /// TODO: Show constructing and executing procedure.
 pub struct Procedure<TCtx> {
+    kind: ProcedureKind,
+    pub(crate) metadata: Vec<Arc<dyn Any + Send + Sync>>,
     handler: Arc<dyn Fn(TCtx, DynInput) -> ProcedureStream + Send + Sync>,
 
     #[cfg(debug_assertions)]
@@ -23,15 +25,41 @@ pub struct Procedure<TCtx> {
 
 impl<TCtx> Procedure<TCtx> {
     pub fn new<F: Fn(TCtx, DynInput) -> ProcedureStream + Send + Sync + 'static>(
+        kind: ProcedureKind,
         handler: F,
     ) -> Self {
         Self {
+            kind,
+            metadata: Vec::new(),
             handler: Arc::new(handler),
             #[cfg(debug_assertions)]
             handler_name: type_name::<F>(),
         }
     }
 
+    pub fn kind(&self) -> ProcedureKind {
+        self.kind
+    }
+
+    /// Attach a value to the procedure which can be read with [`Procedure::metadata`].
+    ///
+    /// Only one value of each type can be attached. Attaching another value of the same type will replace it.
+    pub fn with_metadata<T: Send + Sync + 'static>(self, value: T) -> Self {
+        self.with_metadata_arc(Arc::new(value))
+    }
+
+    /// Attach a value to the procedure which is shared with something else. Refer to [`Procedure::with_metadata`].
+    pub fn with_metadata_arc<T: Send + Sync + 'static>(mut self, value: Arc<T>) -> Self {
+        self.metadata.retain(|v| !v.is::<T>());
+        self.metadata.push(value);
+        self
+    }
+
+    /// Get a value of type `T` attached with [`Procedure::with_metadata`].
+    pub fn metadata<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.metadata.iter().find_map(|v| v.downcast_ref::<T>())
+    }
+
     pub fn exec(&self, ctx: TCtx, input: DynInput) -> ProcedureStream {
         let (Ok(v) | Err(v)) = catch_unwind(AssertUnwindSafe(|| (self.handler)(ctx, input)))
             .map_err(|err| ProcedureError::Unwind(err).into());
@@ -55,6 +83,8 @@ impl<TCtx> Procedure<TCtx> {
 impl<TCtx> Clone for Procedure<TCtx> {
     fn clone(&self) -> Self {
         Self {
+            kind: self.kind,
+            metadata: self.metadata.clone(),
             handler: self.handler.clone(),
             #[cfg(debug_assertions)]
             handler_name: self.handler_name,
@@ -65,6 +95,7 @@ impl<TCtx> Clone for Procedure<TCtx> {
 impl<TCtx> fmt::Debug for Procedure<TCtx> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut t = f.debug_tuple("Procedure");
+        let t = t.field(&self.kind);
         #[cfg(debug_assertions)]
         let t = t.field(&self.handler_name);
         t.finish()
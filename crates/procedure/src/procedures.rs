@@ -6,24 +6,69 @@ use std::{
     sync::Arc,
 };
 
-use crate::{Procedure, State};
+use crate::{LogMessage, Logger, Procedure, ProcedureLayer, State};
 
 pub struct Procedures<TCtx> {
     // TODO: Probally `Arc` around map and share that with `State`?
     procedures: HashMap<Cow<'static, str>, Procedure<TCtx>>,
     state: Arc<State>,
+    logger: Logger,
 }
 
 impl<TCtx> Procedures<TCtx> {
     // TODO: Work out this API. I'm concerned how `rspc_devtools` and `rspc_tracing` fit into this.
     // TODO: Also accept `Into` maybe?
     pub fn new(procedures: HashMap<Cow<'static, str>, Procedure<TCtx>>, state: Arc<State>) -> Self {
-        Self { procedures, state }
+        Self {
+            procedures,
+            state,
+            logger: Logger::default(),
+        }
     }
 
     pub fn state(&self) -> &Arc<State> {
         &self.state
     }
+
+    /// Register a function to log errors which can't be sent to the client. Eg. serialization errors or panics.
+    ///
+    /// Integrations report messages via [`Procedures::logger`].
+    ///
+    /// ```rust
+    /// # fn demo(procedures: rspc_procedure::Procedures<()>) {
+    /// let procedures = procedures.with_logger(|msg| eprintln!("{msg}"));
+    /// # }
+    /// ```
+    pub fn with_logger(mut self, func: impl Fn(LogMessage<'_>) + Send + Sync + 'static) -> Self {
+        self.logger = Logger::new(func);
+        self
+    }
+
+    pub fn logger(&self) -> &Logger {
+        &self.logger
+    }
+
+    /// Wrap every procedure with a [`ProcedureLayer`].
+    ///
+    /// Layers are applied to the procedures which exist at the time this is called.
+    /// If called multiple times the last layer applied will be the outermost.
+    pub fn layer(mut self, layer: impl ProcedureLayer<TCtx>) -> Self
+    where
+        TCtx: 'static,
+    {
+        let layer = Arc::new(layer);
+        for (name, procedure) in self.procedures.iter_mut() {
+            let name = name.clone();
+            let inner = procedure.clone();
+            let layer = layer.clone();
+            let metadata = inner.metadata.clone();
+            *procedure = Procedure::new(inner.kind(), move |ctx, input| {
+                layer.call(&name, &inner, ctx, input)
+            });
+            procedure.metadata = metadata;
+        }
+        self
+    }
 }
 
 // TODO: Should this come back?? `State` makes it rough.
@@ -40,6 +85,7 @@ impl<TCtx> Clone for Procedures<TCtx> {
         Self {
             procedures: self.procedures.clone(),
             state: self.state.clone(),
+            logger: self.logger.clone(),
         }
     }
 }
@@ -1,5 +1,5 @@
 use std::{
-    any::{Any, TypeId},
+    any::{type_name, Any, TypeId},
     collections::HashMap,
     fmt,
     hash::{BuildHasherDefault, Hasher},
@@ -26,39 +26,45 @@ impl Hasher for NoOpHasher {
     }
 }
 
-pub struct State(
-    HashMap<TypeId, Box<dyn Any + Send + Sync + 'static>, BuildHasherDefault<NoOpHasher>>,
-);
+#[derive(Default)]
+pub struct State {
+    inner: HashMap<TypeId, Box<dyn Any + Send + Sync + 'static>, BuildHasherDefault<NoOpHasher>>,
+    required: Vec<(TypeId, &'static str)>,
+}
 
 impl fmt::Debug for State {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_tuple("State").field(&self.0.keys()).finish()
-    }
-}
-
-impl Default for State {
-    fn default() -> Self {
-        Self(Default::default())
+        f.debug_struct("State")
+            .field("keys", &self.inner.keys())
+            .field(
+                "required",
+                &self
+                    .required
+                    .iter()
+                    .map(|(_, name)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
     }
 }
 
 impl State {
     pub fn get<T: Send + Sync + 'static>(&self) -> Option<&T> {
-        self.0.get(&TypeId::of::<T>()).map(|v| {
+        self.inner.get(&TypeId::of::<T>()).map(|v| {
             v.downcast_ref::<T>()
                 .expect("unreachable: TypeId matches but downcast failed")
         })
     }
 
     pub fn get_mut<T: Send + Sync + 'static>(&self) -> Option<&T> {
-        self.0.get(&TypeId::of::<T>()).map(|v| {
+        self.inner.get(&TypeId::of::<T>()).map(|v| {
             v.downcast_ref::<T>()
                 .expect("unreachable: TypeId matches but downcast failed")
         })
     }
 
     pub fn get_or_init<T: Send + Sync + 'static>(&mut self, init: impl FnOnce() -> T) -> &T {
-        self.0
+        self.inner
             .entry(TypeId::of::<T>())
             .or_insert_with(|| Box::new(init()))
             .downcast_ref::<T>()
@@ -69,7 +75,7 @@ impl State {
         &mut self,
         init: impl FnOnce() -> T,
     ) -> &mut T {
-        self.0
+        self.inner
             .entry(TypeId::of::<T>())
             .or_insert_with(|| Box::new(init()))
             .downcast_mut::<T>()
@@ -77,17 +83,35 @@ impl State {
     }
 
     pub fn contains_key<T: Send + Sync + 'static>(&self) -> bool {
-        self.0.contains_key(&TypeId::of::<T>())
+        self.inner.contains_key(&TypeId::of::<T>())
     }
 
     pub fn insert<T: Send + Sync + 'static>(&mut self, t: T) {
-        self.0.insert(TypeId::of::<T>(), Box::new(t));
+        self.inner.insert(TypeId::of::<T>(), Box::new(t));
     }
 
     pub fn remove<T: Send + Sync + 'static>(&mut self) -> Option<T> {
-        self.0.remove(&TypeId::of::<T>()).map(|v| {
+        self.inner.remove(&TypeId::of::<T>()).map(|v| {
             *v.downcast::<T>()
                 .expect("unreachable: TypeId matches but downcast failed")
         })
     }
+
+    /// Mark `T` as required.
+    ///
+    /// This is intended to be called from a setup function so the router can refuse to build if `T` was never provided, instead of it being discovered at request time.
+    pub fn require<T: Send + Sync + 'static>(&mut self) {
+        let id = TypeId::of::<T>();
+        if !self.required.iter().any(|(t, _)| *t == id) {
+            self.required.push((id, type_name::<T>()));
+        }
+    }
+
+    /// Get the type names of all required types which are not currently in the state.
+    pub fn missing(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.required
+            .iter()
+            .filter(|(id, _)| !self.inner.contains_key(id))
+            .map(|(_, name)| *name)
+    }
 }
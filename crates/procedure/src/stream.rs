@@ -17,6 +17,8 @@ use crate::{DynOutput, ProcedureError};
 thread_local! {
     static CAN_FLUSH: RefCell<bool> = RefCell::default();
     static SHOULD_FLUSH: RefCell<Option<bool>> = RefCell::default();
+    // `Some` while polling a stream which has been closed. Set to `Some(true)` once the close is acknowledged.
+    static CLOSING: RefCell<Option<bool>> = RefCell::default();
 }
 
 /// TODO
@@ -36,6 +38,20 @@ pub async fn flush() {
     }
 }
 
+/// Returns `true` if the [`ProcedureStream`] currently being polled has been closed with [`ProcedureStream::close`] (Eg. the client unsubscribed).
+///
+/// Calling this acknowledges the close so the [`ProcedureStream`] will keep polling (discarding any values) until the stream ends, allowing it to cleanup.
+/// If nothing within the stream calls this, it will be dropped on the first poll after being closed.
+pub fn closing() -> bool {
+    CLOSING.with(|v| {
+        let mut v = v.borrow_mut();
+        if v.is_some() {
+            *v = Some(true);
+        }
+        v.is_some()
+    })
+}
+
 enum Inner {
     Dyn(Pin<Box<dyn DynReturnValue>>),
     Value(Option<ProcedureError>),
@@ -55,6 +71,8 @@ pub struct ProcedureStream {
     // This is set `true` if `Poll::Ready` is called while `flush` is `Some`.
     // This informs the stream to yield the value immediately when `flush` is `None` again.
     pending_value: bool, // TODO: Could we just check for a value on `inner`? Less chance of panic in the case of a bug.
+    // If `Some` the stream has been closed. The value is `true` once the stream has acknowledged it via `closing`.
+    closing: Option<bool>,
 }
 
 impl From<ProcedureError> for ProcedureStream {
@@ -63,6 +81,7 @@ impl From<ProcedureError> for ProcedureStream {
             inner: Inner::Value(Some(err)),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 }
@@ -80,22 +99,15 @@ impl ProcedureStream {
                 poll: |s, cx| s.poll_next(cx),
                 size_hint: |s| s.size_hint(),
                 resolved: |_| true,
-                as_value: |v| {
-                    DynOutput::new_serialize(
-                        v.as_mut()
-                            // Error's are caught before `as_value` is called.
-                            .expect("unreachable")
-                            .as_mut()
-                            // Attempted to access value when `Poll::Ready(None)` was not returned.
-                            .expect("unreachable"),
-                    )
-                },
+                // We passthrough the whole `Option` so the value can be taken by in-process callers.
+                as_value: |v| DynOutput::new_serialize_value(v),
                 flushed: false,
                 unwound: false,
                 value: None,
             })),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 
@@ -133,16 +145,8 @@ impl ProcedureStream {
                         (0, Some(0))
                     }
                 },
-                as_value: |v| {
-                    DynOutput::new_serialize(
-                        v.as_mut()
-                            // Error's are caught before `as_value` is called.
-                            .expect("unreachable")
-                            .as_mut()
-                            // Attempted to access value when `Poll::Ready(None)` was not returned.
-                            .expect("unreachable"),
-                    )
-                },
+                // We passthrough the whole `Option` so the value can be taken by in-process callers.
+                as_value: |v| DynOutput::new_serialize_value(v),
                 resolved: |f| f.inner.is_none(),
                 flushed: false,
                 unwound: false,
@@ -150,6 +154,7 @@ impl ProcedureStream {
             })),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 
@@ -193,22 +198,15 @@ impl ProcedureStream {
                 },
                 size_hint: |_| (1, Some(1)),
                 resolved: |f| matches!(f, Repr::Stream { .. }),
-                as_value: |v| {
-                    DynOutput::new_serialize(
-                        v.as_mut()
-                            // Error's are caught before `as_value` is called.
-                            .expect("unreachable")
-                            .as_mut()
-                            // Attempted to access value when `Poll::Ready(None)` was not returned.
-                            .expect("unreachable"),
-                    )
-                },
+                // We passthrough the whole `Option` so the value can be taken by in-process callers.
+                as_value: |v| DynOutput::new_serialize_value(v),
                 flushed: false,
                 unwound: false,
                 value: None,
             })),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 
@@ -232,6 +230,7 @@ impl ProcedureStream {
             })),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 
@@ -277,6 +276,7 @@ impl ProcedureStream {
             })),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 
@@ -327,6 +327,7 @@ impl ProcedureStream {
             })),
             flush: None,
             pending_value: false,
+            closing: None,
         }
     }
 
@@ -367,6 +368,25 @@ impl ProcedureStream {
         }
     }
 
+    /// Close the stream. This should be used when the client unsubscribes or disconnects instead of dropping it.
+    ///
+    /// The stream should continue to be polled until it returns `None` so it has a chance to cleanup (refer to [`closing`]). Any values yielded after this are discarded.
+    /// If the stream doesn't need to cleanup it will end on the next poll.
+    pub fn close(&mut self) {
+        self.closing.get_or_insert(false);
+        self.flush = None;
+        self.pending_value = false;
+    }
+
+    /// Close the stream and drive it until it ends.
+    ///
+    /// This should be used when the client unsubscribes or disconnects so the stream has a chance to cleanup (Eg. `rspc::middleware::Lifecycle` hooks).
+    /// Refer to [`ProcedureStream::close`] for more information.
+    pub async fn close_and_drain(&mut self) {
+        self.close();
+        while poll_fn(|cx| self.poll_inner(cx)).await.is_some() {}
+    }
+
     /// Will return `true` if the future has resolved.
     ///
     /// For a stream created via `Self::from_future*` this will be `true` once the future has resolved and for all other streams this will always be `true`.
@@ -414,31 +434,61 @@ impl ProcedureStream {
             };
         }
 
-        match &mut self.inner {
-            Inner::Dyn(v) => match v.as_mut().poll_next_value(cx) {
-                Poll::Ready(v) => {
-                    if self.flush.is_none() {
-                        Poll::Ready(v)
-                    } else {
-                        match v {
-                            Some(v) => {
-                                self.pending_value = true;
-                                Poll::Pending
-                            }
-                            None => Poll::Ready(None),
-                        }
+        let v = match &mut self.inner {
+            Inner::Dyn(v) => {
+                // We restore the previous value so nested streams don't leak into each other.
+                let prev = CLOSING.replace(self.closing);
+                let v = v.as_mut().poll_next_value(cx);
+                self.closing = CLOSING.replace(prev);
+                v
+            }
+            Inner::Value(v) => Poll::Ready(v.is_some().then_some(())),
+        };
+
+        match self.closing {
+            // The stream doesn't know how to cleanup so we end it now.
+            Some(false) => {
+                self.inner = Inner::Value(None);
+                return Poll::Ready(None);
+            }
+            // The stream is cleaning up so we discard values until it ends.
+            Some(true) => {
+                return match v {
+                    Poll::Ready(Some(())) => {
+                        cx.waker().wake_by_ref();
+                        Poll::Pending
                     }
-                }
-                Poll::Pending => Poll::Pending,
-            },
-            Inner::Value(v) => {
+                    v => v,
+                };
+            }
+            None => {}
+        }
+
+        match v {
+            Poll::Ready(v) => {
                 if self.flush.is_none() {
-                    // Poll::Ready(v.take().map(Err))
-                    todo!();
+                    Poll::Ready(v)
                 } else {
-                    Poll::Pending
+                    match v {
+                        Some(_) => {
+                            self.pending_value = true;
+                            Poll::Pending
+                        }
+                        None => Poll::Ready(None),
+                    }
                 }
             }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    // Get the value yielded by the last call to `poll_inner` that returned `Poll::Ready(Some(()))`.
+    fn value(&mut self) -> Result<DynOutput<'_>, ProcedureError> {
+        match &mut self.inner {
+            Inner::Dyn(s) => s.as_mut().value(),
+            Inner::Value(v) => Err(v
+                .take()
+                .expect("unreachable: `poll_inner` only yields when a value is present")),
         }
     }
 
@@ -447,24 +497,18 @@ impl ProcedureStream {
         &mut self,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<DynOutput<'_>, ProcedureError>>> {
-        self.poll_inner(cx).map(|v| {
-            v.map(|_: ()| {
-                let Inner::Dyn(s) = &mut self.inner else {
-                    unreachable!(); // TODO: Handle this?
-                };
-                s.as_mut().value()
-            })
-        })
+        match self.poll_inner(cx) {
+            Poll::Ready(Some(())) => Poll::Ready(Some(self.value())),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
     }
 
     /// TODO
     pub async fn next(&mut self) -> Option<Result<DynOutput<'_>, ProcedureError>> {
-        poll_fn(|cx| self.poll_inner(cx)).await.map(|_: ()| {
-            let Inner::Dyn(s) = &mut self.inner else {
-                unreachable!(); // TODO: Handle this?
-            };
-            s.as_mut().value()
-        })
+        poll_fn(|cx| self.poll_inner(cx))
+            .await
+            .map(|_: ()| self.value())
     }
 
     /// TODO
@@ -483,6 +527,12 @@ pub struct ProcedureStreamMap<F: FnMut(Result<DynOutput, ProcedureError>) -> Res
 }
 
 impl<F: FnMut(Result<DynOutput, ProcedureError>) -> Result<T, String>, T> ProcedureStreamMap<F, T> {
+    /// Close the stream.
+    /// Refer to [`ProcedureStream::close`] for more information.
+    pub fn close(&mut self) {
+        self.stream.close();
+    }
+
     /// Start streaming data.
     /// Refer to `Self::require_manual_stream` for more information.
     pub fn stream(&mut self) {
@@ -515,11 +565,7 @@ impl<F: FnMut(Result<DynOutput, ProcedureError>) -> Result<T, String> + Unpin, T
 
         this.stream.poll_inner(cx).map(|v| {
             v.map(|_: ()| {
-                let Inner::Dyn(s) = &mut this.stream.inner else {
-                    unreachable!();
-                };
-
-                match (this.map)(s.as_mut().value()) {
+                match (this.map)(this.stream.value()) {
                     Ok(v) => v,
                     // TODO: Exposing this error to the client or not?
                     // TODO: Error type???
@@ -0,0 +1,100 @@
+//! rspc-test: Helpers for unit-testing rspc routers.
+//!
+//! ```rust
+//! use rspc::{Procedure, ProcedureError, Router};
+//! use rspc_test::{assert_procedure_error, TestRouter};
+//! # #[derive(Debug, serde::Serialize, specta::Type)]
+//! # enum Error {}
+//! # impl rspc::Error for Error {
+//! #     fn into_procedure_error(self) -> ProcedureError {
+//! #         match self {}
+//! #     }
+//! # }
+//!
+//! let router = TestRouter::new(<Router>::new().procedure(
+//!     "echo",
+//!     Procedure::builder().query(|_, input: String| async move { Ok::<_, Error>(input) }),
+//! ));
+//!
+//! # futures::executor::block_on(async {
+//! let mut stream = router.call("echo", (), "Hello");
+//! assert_eq!(stream.next_ok().await, "Hello");
+//! stream.assert_done().await;
+//!
+//! assert_procedure_error!(router.call("unknown", (), ()).next().await, ProcedureError::NotFound);
+//! # });
+//! ```
+#![forbid(unsafe_code)]
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc(
+    html_logo_url = "https://github.com/specta-rs/rspc/blob/main/.github/logo.png?raw=true",
+    html_favicon_url = "https://github.com/specta-rs/rspc/blob/main/.github/logo.png?raw=true"
+)]
+// This crate is used within tests so failing with a panic is the expected behaviour.
+#![allow(clippy::panic, clippy::panic_in_result_fn)]
+
+mod router;
+mod snapshot;
+mod stream;
+
+pub use router::TestRouter;
+pub use snapshot::assert_json_snapshot;
+#[cfg(feature = "typescript")]
+#[cfg_attr(docsrs, doc(cfg(feature = "typescript")))]
+pub use snapshot::assert_typescript_snapshot;
+pub use stream::TestStream;
+
+/// Assert the result of a procedure is a [`ProcedureError`](rspc::ProcedureError) matching the pattern.
+///
+/// This accepts a `Result<Value, ProcedureError>` or `Option<Result<Value, ProcedureError>>` (Eg. from [`TestStream::next`]).
+///
+/// ```rust
+/// # use rspc::ProcedureError;
+/// # use rspc_test::assert_procedure_error;
+/// assert_procedure_error!(Err::<(), _>(ProcedureError::NotFound), ProcedureError::NotFound);
+/// ```
+#[macro_export]
+macro_rules! assert_procedure_error {
+    ($result:expr, $pattern:pat $(if $guard:expr)? $(,)?) => {
+        match $crate::__private::IntoTestResult::into_test_result($result) {
+            Err(err) => assert!(
+                matches!(&err, $pattern $(if $guard)?),
+                "expected procedure error matching `{}` but got {err:?}",
+                stringify!($pattern)
+            ),
+            Ok(value) => panic!(
+                "expected procedure error matching `{}` but got value {value:?}",
+                stringify!($pattern)
+            ),
+        }
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    use std::fmt;
+
+    use rspc::ProcedureError;
+
+    pub trait IntoTestResult {
+        type Value: fmt::Debug;
+
+        fn into_test_result(self) -> Result<Self::Value, ProcedureError>;
+    }
+
+    impl<T: fmt::Debug> IntoTestResult for Result<T, ProcedureError> {
+        type Value = T;
+
+        fn into_test_result(self) -> Result<T, ProcedureError> {
+            self
+        }
+    }
+
+    impl<T: fmt::Debug> IntoTestResult for Option<Result<T, ProcedureError>> {
+        type Value = T;
+
+        fn into_test_result(self) -> Result<T, ProcedureError> {
+            self.unwrap_or_else(|| panic!("expected procedure error but the stream has ended"))
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use rspc::{ProcedureError, ProcedureStream, Procedures, Router, State, Types};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::TestStream;
+
+/// A built [`Router`] which can be used to execute procedures within a test.
+pub struct TestRouter<TCtx> {
+    procedures: Procedures<TCtx>,
+    types: Types,
+}
+
+impl<TCtx> TestRouter<TCtx> {
+    /// Build the router.
+    ///
+    /// # Panics
+    ///
+    /// If the router fails to build.
+    #[track_caller]
+    pub fn new(router: Router<TCtx>) -> Self {
+        Self::new_with_state(router, State::default())
+    }
+
+    /// Build the router using a pre-built [`State`]. Refer to [`Router::build_with_state`].
+    ///
+    /// # Panics
+    ///
+    /// If the router fails to build.
+    #[track_caller]
+    pub fn new_with_state(router: Router<TCtx>, state: State) -> Self {
+        match router.build_with_state(state) {
+            Ok((procedures, types)) => Self { procedures, types },
+            Err(errors) => panic!("failed to build router: {errors:?}"),
+        }
+    }
+
+    pub fn procedures(&self) -> &Procedures<TCtx> {
+        &self.procedures
+    }
+
+    pub fn types(&self) -> &Types {
+        &self.types
+    }
+
+    /// Execute a procedure and return the [`TestStream`] of it's results.
+    ///
+    /// The input is serialized to JSON first so it goes through the same deserialization as a request from a client.
+    /// If the procedure doesn't exist the stream will yield a [`ProcedureError::NotFound`].
+    pub fn call(&self, key: &str, ctx: TCtx, input: impl Serialize) -> TestStream {
+        let input = match serde_json::to_value(input) {
+            Ok(input) => input,
+            Err(err) => panic!("failed to serialize input for procedure '{key}': {err}"),
+        };
+
+        TestStream::new(match self.procedures.get(key) {
+            Some(procedure) => procedure.exec_with_deserializer(ctx, input),
+            None => ProcedureStream::from(ProcedureError::NotFound),
+        })
+    }
+
+    /// Execute a procedure and collect every result.
+    pub async fn exec(
+        &self,
+        key: &str,
+        ctx: TCtx,
+        input: impl Serialize,
+    ) -> Vec<Result<Value, ProcedureError>> {
+        self.call(key, ctx, input).collect().await
+    }
+}
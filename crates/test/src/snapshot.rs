@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+/// Assert a value matches the JSON snapshot committed at `path`.
+///
+/// Run the tests with `UPDATE_SNAPSHOTS=1` to create or update the snapshot.
+///
+/// # Panics
+///
+/// If the snapshot doesn't exist or doesn't match.
+#[track_caller]
+pub fn assert_json_snapshot(path: impl AsRef<Path>, value: &impl Serialize) {
+    let actual = match serde_json::to_string_pretty(value) {
+        Ok(actual) => actual + "\n",
+        Err(err) => panic!("failed to serialize snapshot: {err}"),
+    };
+
+    assert_snapshot(path.as_ref(), actual);
+}
+
+/// Assert the output of [`Typescript`](rspc::Typescript) matches the bindings committed at `path`.
+///
+/// Run the tests with `UPDATE_SNAPSHOTS=1` to create or update the snapshot.
+///
+/// # Panics
+///
+/// If the bindings fail to export or the snapshot doesn't exist or doesn't match.
+#[cfg(feature = "typescript")]
+#[track_caller]
+pub fn assert_typescript_snapshot(
+    typescript: &rspc::Typescript,
+    types: &rspc::Types,
+    path: impl AsRef<Path>,
+) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // Tests run in parallel so each export needs it's own directory.
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "rspc-test-{}-{}",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let file = dir.join("bindings.ts");
+
+    let result = std::fs::create_dir_all(&dir)
+        .map_err(Into::into)
+        .and_then(|_| typescript.export_to(&file, types))
+        .and_then(|_| Ok(std::fs::read_to_string(&file)?));
+    let _ = std::fs::remove_dir_all(&dir);
+
+    match result {
+        Ok(actual) => assert_snapshot(path.as_ref(), actual),
+        Err(err) => panic!("failed to export Typescript bindings: {err:?}"),
+    }
+}
+
+#[track_caller]
+fn assert_snapshot(path: &Path, actual: String) {
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(err) = std::fs::write(path, &actual) {
+            panic!("failed to write snapshot '{}': {err}", path.display());
+        }
+        return;
+    }
+
+    let Ok(expected) = std::fs::read_to_string(path) else {
+        panic!(
+            "snapshot '{}' doesn't exist. Run with `{UPDATE_ENV}=1` to create it.",
+            path.display()
+        );
+    };
+
+    // We ignore line endings so snapshots work if git converts them on Windows.
+    if expected.replace("\r\n", "\n") != actual.replace("\r\n", "\n") {
+        panic!(
+            "snapshot '{}' doesn't match. Run with `{UPDATE_ENV}=1` to update it.\n\nExpected:\n{expected}\n\nActual:\n{actual}",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,91 @@
+use std::task::{Context, Poll, Waker};
+
+use rspc::{DynOutput, ProcedureError, ProcedureStream};
+use serde_json::Value;
+
+/// The results of a procedure being executed, converted to JSON.
+///
+/// For subscriptions this can be driven step by step with [`TestStream::poll_next`].
+pub struct TestStream {
+    stream: ProcedureStream,
+}
+
+impl TestStream {
+    pub fn new(stream: ProcedureStream) -> Self {
+        Self { stream }
+    }
+
+    /// Wait for the next result.
+    ///
+    /// Returns `None` once the stream has ended.
+    pub async fn next(&mut self) -> Option<Result<Value, ProcedureError>> {
+        self.stream.next().await.map(to_json)
+    }
+
+    /// Poll the stream once without waiting.
+    ///
+    /// This returns `Poll::Pending` if the procedure has not yielded it's next value yet.
+    pub fn poll_next(&mut self) -> Poll<Option<Result<Value, ProcedureError>>> {
+        self.stream
+            .poll_next(&mut Context::from_waker(Waker::noop()))
+            .map(|v| v.map(to_json))
+    }
+
+    /// Close the stream like a client unsubscribing would.
+    ///
+    /// Continue calling [`TestStream::next`] until it returns `None` to run the stream's cleanup. Refer to [`ProcedureStream::close`].
+    pub fn close(&mut self) {
+        self.stream.close();
+    }
+
+    /// Wait for the next result, expecting it to be a value.
+    ///
+    /// # Panics
+    ///
+    /// If the procedure returns an error or the stream has ended.
+    pub async fn next_ok(&mut self) -> Value {
+        match self.next().await {
+            Some(Ok(value)) => value,
+            Some(Err(err)) => panic!("expected value but procedure returned error {err:?}"),
+            None => panic!("expected value but the stream has ended"),
+        }
+    }
+
+    /// Wait for the next result, expecting it to be an error.
+    ///
+    /// # Panics
+    ///
+    /// If the procedure returns a value or the stream has ended.
+    pub async fn next_err(&mut self) -> ProcedureError {
+        match self.next().await {
+            Some(Ok(value)) => panic!("expected error but procedure returned value {value}"),
+            Some(Err(err)) => err,
+            None => panic!("expected error but the stream has ended"),
+        }
+    }
+
+    /// Assert the stream has no more results.
+    ///
+    /// # Panics
+    ///
+    /// If the stream yields another result.
+    pub async fn assert_done(&mut self) {
+        if let Some(result) = self.next().await {
+            panic!("expected the stream to have ended but got {result:?}");
+        }
+    }
+
+    /// Wait for every remaining result.
+    pub async fn collect(mut self) -> Vec<Result<Value, ProcedureError>> {
+        let mut results = Vec::new();
+        while let Some(result) = self.next().await {
+            results.push(result);
+        }
+        results
+    }
+}
+
+// If the output fails to serialize this returns a `ProcedureError::Serializer` like an integration would.
+fn to_json(result: Result<DynOutput, ProcedureError>) -> Result<Value, ProcedureError> {
+    result.and_then(|output| output.serialize_with(serde_json::value::Serializer))
+}
@@ -0,0 +1,63 @@
+use std::task::Poll;
+
+use futures::{channel::mpsc, SinkExt};
+use rspc::{Procedure, ProcedureError, Router};
+use rspc_test::{assert_json_snapshot, TestRouter};
+use serde_json::json;
+
+#[derive(Debug, serde::Serialize, specta::Type)]
+enum Error {}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        match self {}
+    }
+}
+
+#[test]
+fn subscription_step_by_step() {
+    let (mut tx, rx) = mpsc::channel::<u32>(1);
+    let rx = std::sync::Mutex::new(Some(rx));
+    let router = TestRouter::new(<Router>::new().procedure(
+        "numbers",
+        Procedure::builder().subscription(move |_, _: ()| {
+            let rx = rx.lock().unwrap().take().unwrap();
+            async move { Ok::<_, Error>(rspc::Stream(futures::StreamExt::map(rx, Ok))) }
+        }),
+    ));
+
+    let mut stream = router.call("numbers", (), ());
+    assert!(stream.poll_next().is_pending());
+
+    futures::executor::block_on(async {
+        tx.send(1).await.unwrap();
+        assert_eq!(stream.next_ok().await, json!(1));
+        assert!(stream.poll_next().is_pending());
+
+        tx.send(2).await.unwrap();
+        drop(tx);
+        assert!(matches!(stream.poll_next(), Poll::Ready(Some(Ok(v))) if v == json!(2)));
+        stream.assert_done().await;
+    });
+}
+
+#[test]
+fn snapshots() {
+    let router = TestRouter::new(<Router>::new().procedure(
+        "version",
+        Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>("1.0.0") }),
+    ));
+
+    let results = futures::executor::block_on(router.exec("version", (), ()));
+    assert_json_snapshot(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/version.json"),
+        &results.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+    );
+
+    #[cfg(feature = "typescript")]
+    rspc_test::assert_typescript_snapshot(
+        &rspc::Typescript::default(),
+        router.types(),
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/bindings.ts"),
+    );
+}
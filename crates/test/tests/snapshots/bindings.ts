@@ -0,0 +1,18 @@
+// This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.
+
+/**
+ * The input to a procedure failed to deserialize.
+ * 
+ * `path` is the location of the field which caused the error (Eg. `["users", 0, "name"]`).
+ */
+export type DeserializeError = { "~rspc": true; variant: "Deserialize"; message: string; path: PathSegment[]; expected: string | null; found: string | null }
+
+export type Error = never
+
+export type PathSegment = string | number
+
+export type ProceduresLegacy = { queries: { key: "version"; input: null; result: string }; mutations: never; subscriptions: never }
+
+export type Procedures = {
+	version: { kind: "query", input: null, output: string, error: Error },
+}
\ No newline at end of file
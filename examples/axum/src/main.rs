@@ -27,7 +27,7 @@ async fn main() {
     rspc::Typescript::default()
         // .formatter(specta_typescript::formatter::prettier)
         .header("// My custom header")
-        // .enable_source_maps() // TODO: Fix this
+        .enable_source_maps()
         .export_to(
             PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../bindings.ts"),
             &types,
@@ -35,13 +35,14 @@ async fn main() {
         .unwrap();
 
     // Be aware this is very experimental and doesn't support many types yet.
-    // rspc::Rust::default()
-    //     // .header("// My custom header")
-    //     .export_to(
-    //         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../client/src/bindings.rs"),
-    //         &types,
-    //     )
-    //     .unwrap();
+    rspc::Rust::default()
+        // .header("// My custom header")
+        .formatter(rspc::Rust::rustfmt)
+        .export_to(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../client/src/bindings.rs"),
+            &types,
+        )
+        .unwrap();
 
     // let procedures = rspc_devtools::mount(procedures, &types); // TODO
 
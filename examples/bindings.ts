@@ -1,19 +1,17 @@
 // My custom header
 // This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.
 
+/**
+ * The input to a procedure failed to deserialize.
+ * 
+ * `path` is the location of the field which caused the error (Eg. `["users", 0, "name"]`).
+ */
+export type DeserializeError = { "~rspc": true; variant: "Deserialize"; message: string; path: PathSegment[]; expected: string | null; found: string | null }
+
 export type Error = { type: "Mistake"; error: string } | { type: "Validator"; error: any } | { type: "Authorization"; error: string }
 
+export type PathSegment = string | number
+
 export type ProceduresLegacy = { queries: { key: "cached"; input: null; result: number } | { key: "newstuff"; input: null; result: string } | { key: "newstuff2"; input: null; result: string } | { key: "newstuffpanic"; input: null; result: null } | { key: "newstuffser"; input: null; result: null } | { key: "sendMsg"; input: string; result: string } | { key: "sfmPost"; input: [string, null]; result: string } | { key: "streamInStreamInStreamInStream"; input: null; result: number } | { key: "validator"; input: { mail: string }; result: null } | { key: "withoutBaseProcedure"; input: string; result: null }; mutations: never; subscriptions: never }
 
-export type Procedures = {
-	cached: { kind: "query", input: null, output: number, error: Error },
-	newstuff: { kind: "query", input: null, output: string, error: Error },
-	newstuff2: { kind: "query", input: null, output: string, error: Error },
-	newstuffpanic: { kind: "query", input: null, output: null, error: Error },
-	newstuffser: { kind: "query", input: null, output: null, error: Error },
-	sendMsg: { kind: "query", input: string, output: string, error: Error },
-	sfmPost: { kind: "query", input: [string, null], output: string, error: Error },
-	streamInStreamInStreamInStream: { kind: "query", input: null, output: number, error: Error },
-	validator: { kind: "query", input: { mail: string }, output: null, error: Error },
-	withoutBaseProcedure: { kind: "query", input: string, output: null, error: Error },
-}
\ No newline at end of file
+export type { Procedures } from './bindings_t';
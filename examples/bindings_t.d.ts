@@ -1,16 +1,18 @@
 // My custom header
 // This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.
 
+import type { Error } from './bindings';
+
 export type Procedures = {
-	echo: { input: string, output: string, error: unknown },
-	error: { input: null, output: string, error: unknown },
-	nested: {
-	hello: { input: null, output: string, error: unknown },
-},
-	newstuff: { input: any, output: any, error: any },
-	pings: { input: null, output: string, error: unknown },
-	sendMsg: { input: string, output: string, error: unknown },
-	transformMe: { input: null, output: string, error: unknown },
-	version: { input: null, output: string, error: unknown },
+	cached: { kind: "query", input: null, output: number, error: Error },
+	newstuff: { kind: "query", input: null, output: string, error: Error },
+	newstuff2: { kind: "query", input: null, output: string, error: Error },
+	newstuffpanic: { kind: "query", input: null, output: null, error: Error },
+	newstuffser: { kind: "query", input: null, output: null, error: Error },
+	sendMsg: { kind: "query", input: string, output: string, error: Error },
+	sfmPost: { kind: "query", input: [string, null], output: string, error: Error },
+	streamInStreamInStreamInStream: { kind: "query", input: null, output: number, error: Error },
+	validator: { kind: "query", input: { mail: string }, output: null, error: Error },
+	withoutBaseProcedure: { kind: "query", input: string, output: null, error: Error },
 }
-//# sourceMappingURL=bindings_t.d.ts.map
\ No newline at end of file
+//# sourceMappingURL=bindings_t.d.ts.map
@@ -1,53 +1,110 @@
 //! This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.
 
+#![allow(
+    non_camel_case_types,
+    non_snake_case,
+    dead_code,
+    unused_imports,
+    deprecated
+)]
+
 pub struct Procedures;
 
-pub struct echo;
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    rspc_client::internal::serde::Serialize,
+    rspc_client::internal::serde::Deserialize,
+)]
+#[serde(crate = "rspc_client::internal::serde")]
+#[serde(tag = "type", content = "error")]
+pub enum Error {
+    Mistake(String),
+    Validator(rspc_client::internal::serde_json::Value),
+    Authorization(String),
+}
 
-impl rspc_client::Procedure for echo {
-    type Input = String;
-    type Output = String;
-    type Error = ();
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    rspc_client::internal::serde::Serialize,
+    rspc_client::internal::serde::Deserialize,
+)]
+#[serde(crate = "rspc_client::internal::serde")]
+pub struct SerialisationError;
+
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    rspc_client::internal::serde::Serialize,
+    rspc_client::internal::serde::Deserialize,
+)]
+#[serde(crate = "rspc_client::internal::serde")]
+pub struct ValidatedType {
+    pub mail: String,
+}
+
+pub struct cached;
+
+impl rspc_client::Procedure for cached {
+    type Input = ();
+    type Output = i32;
+    type Error = Error;
     type Procedures = Procedures;
     const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
-    const KEY: &'static str = "echo";
+    const KEY: &'static str = "cached";
 }
 
-pub struct error;
+pub struct newstuff;
 
-impl rspc_client::Procedure for error {
+impl rspc_client::Procedure for newstuff {
     type Input = ();
     type Output = String;
-    type Error = ();
+    type Error = Error;
     type Procedures = Procedures;
     const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
-    const KEY: &'static str = "error";
+    const KEY: &'static str = "newstuff";
 }
 
+pub struct newstuff2;
 
-pub mod hello {
-	pub use super::Procedures;
-pub struct hello;
-
-impl rspc_client::Procedure for hello {
+impl rspc_client::Procedure for newstuff2 {
     type Input = ();
     type Output = String;
-    type Error = ();
+    type Error = Error;
     type Procedures = Procedures;
     const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
-    const KEY: &'static str = "hello";
+    const KEY: &'static str = "newstuff2";
 }
 
+pub struct newstuffpanic;
+
+impl rspc_client::Procedure for newstuffpanic {
+    type Input = ();
+    type Output = ();
+    type Error = Error;
+    type Procedures = Procedures;
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
+    const KEY: &'static str = "newstuffpanic";
 }
-pub struct pings;
 
-impl rspc_client::Procedure for pings {
+pub struct newstuffser;
+
+impl rspc_client::Procedure for newstuffser {
     type Input = ();
-    type Output = String;
-    type Error = ();
+    type Output = SerialisationError;
+    type Error = Error;
     type Procedures = Procedures;
-    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Subscription;
-    const KEY: &'static str = "pings";
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
+    const KEY: &'static str = "newstuffser";
 }
 
 pub struct sendMsg;
@@ -55,31 +112,52 @@ pub struct sendMsg;
 impl rspc_client::Procedure for sendMsg {
     type Input = String;
     type Output = String;
-    type Error = ();
+    type Error = Error;
     type Procedures = Procedures;
-    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Mutation;
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
     const KEY: &'static str = "sendMsg";
 }
 
-pub struct transformMe;
+pub struct sfmPost;
 
-impl rspc_client::Procedure for transformMe {
-    type Input = ();
+impl rspc_client::Procedure for sfmPost {
+    type Input = (String, ());
     type Output = String;
-    type Error = ();
+    type Error = Error;
     type Procedures = Procedures;
     const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
-    const KEY: &'static str = "transformMe";
+    const KEY: &'static str = "sfmPost";
 }
 
-pub struct version;
+pub struct streamInStreamInStreamInStream;
 
-impl rspc_client::Procedure for version {
+impl rspc_client::Procedure for streamInStreamInStreamInStream {
     type Input = ();
-    type Output = String;
-    type Error = ();
+    type Output = i32;
+    type Error = Error;
+    type Procedures = Procedures;
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
+    const KEY: &'static str = "streamInStreamInStreamInStream";
+}
+
+pub struct validator;
+
+impl rspc_client::Procedure for validator {
+    type Input = ValidatedType;
+    type Output = ();
+    type Error = Error;
     type Procedures = Procedures;
     const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
-    const KEY: &'static str = "version";
+    const KEY: &'static str = "validator";
 }
 
+pub struct withoutBaseProcedure;
+
+impl rspc_client::Procedure for withoutBaseProcedure {
+    type Input = String;
+    type Output = ();
+    type Error = Error;
+    type Procedures = Procedures;
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
+    const KEY: &'static str = "withoutBaseProcedure";
+}
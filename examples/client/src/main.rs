@@ -5,11 +5,11 @@ mod bindings;
 async fn main() {
     let client = rspc_client::Client::new("http://[::]:4000/rspc");
 
-    println!("{:?}", client.exec::<bindings::version>(()).await);
+    println!("{:?}", client.exec::<bindings::newstuff>(()).await);
     println!(
         "{:?}",
         client
-            .exec::<bindings::echo>("Some random string!".into())
+            .exec::<bindings::withoutBaseProcedure>("Some random string!".into())
             .await
     );
     println!(
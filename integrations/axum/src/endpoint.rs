@@ -1,225 +1,325 @@
-// use std::{
-//     convert::Infallible,
-//     future::poll_fn,
-//     pin::Pin,
-//     sync::Arc,
-//     task::{Context, Poll},
-// };
-
-// use axum::{
-//     body::{Body, Bytes, HttpBody},
-//     extract::{FromRequest, Request},
-//     http::{header, HeaderMap, StatusCode},
-//     response::{
-//         sse::{Event, KeepAlive},
-//         IntoResponse, Sse,
-//     },
-//     routing::{on, MethodFilter},
-// };
-// use futures::{stream::once, Stream, StreamExt, TryStreamExt};
-// use rspc_procedure::{ProcedureError, ProcedureStream, Procedures};
-// use rspc_http::ExecuteInput;
-
-// /// Construct a new [`axum::Router`](axum::Router) to expose a given [`rspc::Router`](rspc::Router).
-// pub struct Endpoint<TCtx> {
-//     procedures: Procedures<TCtx>,
-//     // endpoints: bool,
-//     // websocket: Option<fn(&TCtx) -> TCtx>,
-//     // batching: bool,
-// }
-
-// impl<TCtx: Send + 'static> Endpoint<TCtx> {
-//     // /// Construct a new [`axum::Router`](axum::Router) with all features enabled.
-//     // ///
-//     // /// This will enable all features, if you want to configure which features are enabled you can use [`Endpoint::builder`] instead.
-//     // ///
-//     // /// # Usage
-//     // ///
-//     // /// ```rust
-//     // /// axum::Router::new().nest(
-//     // ///     "/rspc",
-//     // ///     rspc_axum::Endpoint::new(rspc::Router::new().build().unwrap(), || ()),
-//     // /// );
-//     // /// ```
-//     // pub fn new<S>(
-//     //     router: BuiltRouter<TCtx>,
-//     //     // TODO: Parse this to `Self::build` -> It will make rustfmt result way nicer
-//     //     // TODO: Make Axum extractors work
-//     //     ctx_fn: impl Fn(&Parts) -> TCtx + Send + Sync + 'static,
-//     // ) -> axum::Router<S>
-//     // where
-//     //     S: Clone + Send + Sync + 'static,
-//     //     // TODO: Error type???
-//     //     // F: Future<Output = Result<TCtx, ()>> + Send + Sync + 'static,
-//     //     TCtx: Clone,
-//     // {
-//     //     let mut t = Self::builder(router).with_endpoints();
-//     //     #[cfg(feature = "ws")]
-//     //     {
-//     //         t = t.with_websocket();
-//     //     }
-//     //     t.with_batching().build(ctx_fn)
-//     // }
-
-//     // /// Construct a new [`Endpoint`](Endpoint) with no features enabled.
-//     // ///
-//     // /// # Usage
-//     // ///
-//     // /// ```rust
-//     // /// axum::Router::new().nest(
-//     // ///     "/rspc",
-//     // ///     rspc_axum::Endpoint::builder(rspc::Router::new().build().unwrap())
-//     // ///         // Exposes HTTP endpoints for queries and mutations.
-//     // ///         .with_endpoints()
-//     // ///         // Exposes a Websocket connection for queries, mutations and subscriptions.
-//     // ///         .with_websocket()
-//     // ///         // Enables support for the frontend sending batched queries.
-//     // ///         .with_batching()
-//     // ///         .build(|| ()),
-//     // /// );
-//     // /// ```
-//     pub fn builder(router: Procedures<TCtx>) -> Self {
-//         Self {
-//             procedures: router,
-//             // endpoints: false,
-//             // websocket: None,
-//             // batching: false,
-//         }
-//     }
-
-//     // /// Enables HTTP endpoints for queries and mutations.
-//     // ///
-//     // /// This is exposed as `/routerName.procedureName`
-//     // pub fn with_endpoints(mut self) -> Self {
-//     //     Self {
-//     //         endpoints: true,
-//     //         ..self
-//     //     }
-//     // }
-
-//     // /// Exposes a Websocket connection for queries, mutations and subscriptions.
-//     // ///
-//     // /// This is exposed as a `/ws` endpoint.
-//     // #[cfg(feature = "ws")]
-//     // #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
-//     // pub fn with_websocket(self) -> Self
-//     // where
-//     //     TCtx: Clone,
-//     // {
-//     //     Self {
-//     //         websocket: Some(|ctx| ctx.clone()),
-//     //         ..self
-//     //     }
-//     // }
-
-//     // /// Enables support for the frontend sending batched queries.
-//     // ///
-//     // /// This is exposed as a `/_batch` endpoint.
-//     // pub fn with_batching(self) -> Self
-//     // where
-//     //     TCtx: Clone,
-//     // {
-//     //     Self {
-//     //         batching: true,
-//     //         ..self
-//     //     }
-//     // }
-
-//     // TODO: Axum extractors
-
-//     /// Build an [`axum::Router`](axum::Router) with the configured features.
-//     pub fn build<S>(self, ctx_fn: impl Fn() -> TCtx + Send + Sync + 'static) -> axum::Router<S>
-//     where
-//         S: Clone + Send + Sync + 'static,
-//     {
-//         let mut r = axum::Router::new();
-//         let ctx_fn = Arc::new(ctx_fn);
-
-//         // let logger = self.procedures.get_logger();
-
-//         for (key, procedure) in self.procedures {
-//             let ctx_fn = ctx_fn.clone();
-//             r = r.route(
-//                 &format!("/{key}"),
-//                 on(
-//                     MethodFilter::GET.or(MethodFilter::POST),
-//                     move |req: Request| {
-//                         // let ctx = ctx_fn();
-
-//                         async move {
-//                             let hint = req.body().size_hint();
-//                             let has_body = hint.lower() != 0 || hint.upper() != Some(0);
-
-//                             let mut bytes = None;
-//                             let input = if !has_body {
-//                                 ExecuteInput::Query(req.uri().query().unwrap_or_default())
-//                             } else {
-//                                 // TODO: bring this back
-//                                 // if !json_content_type(req.headers()) {
-//                                 //     let err: ProcedureError = rspc_procedure::DeserializeError::custom(
-//                                 //         "Client did not set correct valid 'Content-Type' header",
-//                                 //     )
-//                                 //     .into();
-//                                 //     let buf = serde_json::to_vec(&err).unwrap(); // TODO
-
-//                                 //     return (
-//                                 //         StatusCode::BAD_REQUEST,
-//                                 //         [(header::CONTENT_TYPE, "application/json")],
-//                                 //         Body::from(buf),
-//                                 //     )
-//                                 //         .into_response();
-//                                 // }
-
-//                                 // TODO: Error handling
-//                                 bytes = Some(Bytes::from_request(req, &()).await.unwrap());
-//                                 ExecuteInput::Body(
-//                                     bytes.as_ref().expect("assigned on previous line"),
-//                                 )
-//                             };
-
-//                             let (status, stream) =
-//                                 rspc_http::execute(&procedure, input, || ctx_fn()).await;
-
-//                             (
-//                                 StatusCode::from_u16(status)
-//                                     .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
-//                                 [(header::CONTENT_TYPE, "application/json")],
-//                                 Body::from_stream(stream.map(Ok::<_, Infallible>)),
-//                             )
-//                                 .into_response()
-//                         }
-//                     },
-//                 ),
-//             );
-//         }
-
-//         // TODO: Websocket endpoint
-
-//         r
-//     }
-// }
-
-// fn json_content_type(headers: &HeaderMap) -> bool {
-//     let content_type = if let Some(content_type) = headers.get(header::CONTENT_TYPE) {
-//         content_type
-//     } else {
-//         return false;
-//     };
-
-//     let content_type = if let Ok(content_type) = content_type.to_str() {
-//         content_type
-//     } else {
-//         return false;
-//     };
-
-//     let mime = if let Ok(mime) = content_type.parse::<mime::Mime>() {
-//         mime
-//     } else {
-//         return false;
-//     };
-
-//     let is_json_content_type = mime.type_() == "application"
-//         && (mime.subtype() == "json" || mime.suffix().map_or(false, |name| name == "json"));
-
-//     is_json_content_type
-// }
+use std::borrow::Borrow;
+
+use axum::{
+    extract::{DefaultBodyLimit, Request, State},
+    http::Method,
+    routing::{any, post},
+    Router,
+};
+use rspc_procedure::Procedures;
+
+use crate::{
+    extractors::TCtxFunc,
+    jsonrpc::ProcedureKind,
+    v2::{handle_batch, handle_procedure},
+};
+
+/// The default for [`Endpoint::with_max_body_size`].
+const DEFAULT_MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+/// The default for [`Endpoint::with_max_query_size`].
+const DEFAULT_MAX_QUERY_SIZE: usize = 8 * 1024;
+/// The default for [`Endpoint::with_max_json_depth`].
+const DEFAULT_MAX_JSON_DEPTH: usize = 64;
+/// The default for [`Endpoint::with_max_batch_size`].
+const DEFAULT_MAX_BATCH_SIZE: usize = 100;
+
+/// Construct a new [`axum::Router`](axum::Router) to expose a given [`rspc::Router`](https://docs.rs/rspc/latest/rspc/struct.Router.html).
+///
+/// Requests and responses are always encoded as JSON. Choosing a different wire format is not supported yet.
+pub struct Endpoint<TCtx> {
+    procedures: Procedures<TCtx>,
+    #[cfg(feature = "ws")]
+    websocket: Option<String>,
+    batching: bool,
+    options: HttpOptions,
+}
+
+/// The options used by the HTTP handlers.
+#[derive(Debug, Clone)]
+pub(crate) struct HttpOptions {
+    pub(crate) endpoints: bool,
+    pub(crate) sse: bool,
+    pub(crate) max_body_size: usize,
+    pub(crate) max_query_size: usize,
+    pub(crate) max_json_depth: usize,
+    pub(crate) max_batch_size: usize,
+    pub(crate) query_methods: Vec<Method>,
+    pub(crate) mutation_methods: Vec<Method>,
+}
+
+impl HttpOptions {
+    /// The methods a procedure of this kind can be called with over HTTP.
+    pub(crate) fn methods(&self, kind: ProcedureKind) -> &[Method] {
+        match kind {
+            ProcedureKind::Query => &self.query_methods,
+            ProcedureKind::Mutation => &self.mutation_methods,
+            // These are only supported using a websocket or Server-Sent Events.
+            ProcedureKind::Subscription => &[],
+        }
+    }
+}
+
+impl<TCtx: Send + Sync + 'static> Endpoint<TCtx> {
+    /// Construct a new [`axum::Router`](axum::Router) with all features enabled.
+    ///
+    /// This will enable all features, if you want to configure which features are enabled you can use [`Endpoint::builder`] instead.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// let (procedures, _types) = <rspc::Router>::new().build().unwrap();
+    ///
+    /// let app: axum::Router = axum::Router::new().nest("/rspc", rspc_axum::Endpoint::new(procedures, || ()));
+    /// ```
+    #[allow(clippy::new_ret_no_self)] // This is a shortcut for `Endpoint::builder(..).build(..)`
+    pub fn new<S, TCtxFnMarker, TCtxFn>(
+        procedures: impl Borrow<Procedures<TCtx>>,
+        ctx_fn: TCtxFn,
+    ) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+        TCtxFnMarker: Send + Sync + 'static,
+        TCtxFn: TCtxFunc<TCtx, S, TCtxFnMarker>,
+    {
+        let endpoint = Self::builder(procedures).with_endpoints();
+        #[cfg(feature = "ws")]
+        let endpoint = endpoint.with_websocket();
+        endpoint.with_batching().with_sse().build(ctx_fn)
+    }
+
+    /// Construct a new [`Endpoint`](Endpoint) with no features enabled.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// let (procedures, _types) = <rspc::Router>::new().build().unwrap();
+    ///
+    /// let app: axum::Router = axum::Router::new().nest(
+    ///     "/rspc",
+    ///     rspc_axum::Endpoint::builder(procedures)
+    ///         // Exposes HTTP endpoints for queries and mutations.
+    ///         .with_endpoints()
+    ///         // Enables support for the frontend sending batched queries.
+    ///         .with_batching()
+    ///         // Exposes queries and subscriptions as Server-Sent Events.
+    ///         .with_sse()
+    ///         .build(|| ()),
+    /// );
+    /// ```
+    pub fn builder(procedures: impl Borrow<Procedures<TCtx>>) -> Self {
+        Self {
+            procedures: procedures.borrow().clone(),
+            #[cfg(feature = "ws")]
+            websocket: None,
+            batching: false,
+            options: HttpOptions {
+                endpoints: false,
+                sse: false,
+                max_body_size: DEFAULT_MAX_BODY_SIZE,
+                max_query_size: DEFAULT_MAX_QUERY_SIZE,
+                max_json_depth: DEFAULT_MAX_JSON_DEPTH,
+                max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+                query_methods: vec![Method::GET],
+                mutation_methods: vec![Method::POST],
+            },
+        }
+    }
+
+    /// Enables HTTP endpoints for queries and mutations.
+    ///
+    /// This is exposed as `/routerName.procedureName`
+    pub fn with_endpoints(mut self) -> Self {
+        self.options.endpoints = true;
+        self
+    }
+
+    /// Exposes a Websocket connection for queries, mutations and subscriptions.
+    ///
+    /// This is exposed as a `/ws` endpoint.
+    #[cfg(feature = "ws")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
+    pub fn with_websocket(self) -> Self {
+        self.with_websocket_path("/ws")
+    }
+
+    /// Exposes a Websocket connection for queries, mutations and subscriptions at a custom path.
+    ///
+    /// The path is relative to where the router is nested. Eg. `/socket`.
+    #[cfg(feature = "ws")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "ws")))]
+    pub fn with_websocket_path(self, path: impl Into<String>) -> Self {
+        Self {
+            websocket: Some(path.into()),
+            ..self
+        }
+    }
+
+    /// Enables support for the frontend sending batched queries.
+    ///
+    /// This is exposed as a `/_batch` endpoint.
+    pub fn with_batching(self) -> Self {
+        Self {
+            batching: true,
+            ..self
+        }
+    }
+
+    /// Exposes queries and subscriptions as [Server-Sent Events](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events).
+    ///
+    /// This is used for `GET` requests to `/routerName.procedureName` which accept `text/event-stream`.
+    pub fn with_sse(mut self) -> Self {
+        self.options.sse = true;
+        self
+    }
+
+    /// Set the maximum size of a request body or websocket message in bytes. This defaults to 2MB.
+    ///
+    /// Requests with a larger body are rejected with a `413 Payload Too Large` error.
+    pub fn with_max_body_size(mut self, max_body_size: usize) -> Self {
+        self.options.max_body_size = max_body_size;
+        self
+    }
+
+    /// Set the maximum size of the query string of a `GET` request in bytes. This defaults to 8KB.
+    ///
+    /// Requests with a larger query string are rejected with a `413 Payload Too Large` error.
+    pub fn with_max_query_size(mut self, max_query_size: usize) -> Self {
+        self.options.max_query_size = max_query_size;
+        self
+    }
+
+    /// Set how deeply arrays and objects can be nested within the input of a procedure. This defaults to 64.
+    ///
+    /// Requests with a more deeply nested input are rejected with a `400 Bad Request` error.
+    pub fn with_max_json_depth(mut self, max_json_depth: usize) -> Self {
+        self.options.max_json_depth = max_json_depth;
+        self
+    }
+
+    /// Set the maximum number of requests in a batch. This defaults to 100.
+    ///
+    /// Larger batches are rejected with a `413 Payload Too Large` error.
+    pub fn with_max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.options.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Set the HTTP methods which can be used to call a query. This defaults to `GET`.
+    ///
+    /// The input is read from the `input` query parameter for `GET` requests and from the body for any other method.
+    pub fn with_query_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.options.query_methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Set the HTTP methods which can be used to call a mutation. This defaults to `POST`.
+    ///
+    /// The input is read from the `input` query parameter for `GET` requests and from the body for any other method.
+    /// Allowing `GET` is not recommended as it makes the mutation vulnerable to CSRF.
+    pub fn with_mutation_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.options.mutation_methods = methods.into_iter().collect();
+        self
+    }
+
+    /// Build an [`axum::Router`](axum::Router) with the configured features.
+    ///
+    /// The context function can take Axum extractors and be async. If it returns an error it's sent to the client with the error's code as the HTTP status.
+    ///
+    /// # Usage
+    ///
+    /// ```rust
+    /// use axum::http::HeaderMap;
+    /// use rspc::{ErrorCode, ProcedureError, ResolverError};
+    ///
+    /// #[derive(Debug, serde::Serialize)]
+    /// enum AuthError {
+    ///     MissingToken,
+    /// }
+    ///
+    /// impl From<AuthError> for ProcedureError {
+    ///     fn from(err: AuthError) -> Self {
+    ///         ResolverError::new(err, None::<std::io::Error>)
+    ///             .with_code(ErrorCode::Unauthorized)
+    ///             .into()
+    ///     }
+    /// }
+    ///
+    /// let (procedures, _types) = <rspc::Router<String>>::new().build().unwrap();
+    ///
+    /// let app: axum::Router = axum::Router::new().nest(
+    ///     "/rspc",
+    ///     rspc_axum::Endpoint::builder(procedures)
+    ///         .with_endpoints()
+    ///         .build(|headers: HeaderMap| async move {
+    ///             headers
+    ///                 .get("authorization")
+    ///                 .and_then(|v| v.to_str().ok())
+    ///                 .map(ToString::to_string)
+    ///                 .ok_or(AuthError::MissingToken)
+    ///         }),
+    /// );
+    /// ```
+    pub fn build<S, TCtxFnMarker, TCtxFn>(self, ctx_fn: TCtxFn) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+        TCtxFnMarker: Send + Sync + 'static,
+        TCtxFn: TCtxFunc<TCtx, S, TCtxFnMarker>,
+    {
+        let mut router = Router::<S>::new();
+        let max_body_size = self.options.max_body_size;
+
+        #[cfg(feature = "ws")]
+        if let Some(path) = &self.websocket {
+            let procedures = self.procedures.clone();
+            let ctx_fn = ctx_fn.clone();
+            let max_json_depth = self.options.max_json_depth;
+            router = router.route(
+                path,
+                axum::routing::get(
+                    move |state: State<S>,
+                          upgrade: axum::extract::ws::WebSocketUpgrade,
+                          req: Request| async move {
+                        let parts = req.into_parts().0;
+                        upgrade
+                            .max_message_size(max_body_size)
+                            .on_upgrade(move |socket| {
+                                crate::v2::handle_websocket(
+                                    ctx_fn,
+                                    socket,
+                                    parts,
+                                    procedures,
+                                    state.0,
+                                    max_json_depth,
+                                )
+                            })
+                    },
+                ),
+            );
+        }
+
+        if self.batching {
+            let procedures = self.procedures.clone();
+            let ctx_fn = ctx_fn.clone();
+            let options = self.options.clone();
+            router = router.route(
+                "/_batch",
+                post(move |state: State<S>, req: Request| async move {
+                    handle_batch(ctx_fn, req, &procedures, &options, state.0).await
+                }),
+            );
+        }
+
+        if self.options.endpoints || self.options.sse {
+            let procedures = self.procedures;
+            let options = self.options;
+            router = router.route(
+                "/{id}",
+                any(move |state: State<S>, req: Request| async move {
+                    handle_procedure(ctx_fn, req, &procedures, &options, state.0).await
+                }),
+            );
+        }
+
+        router.layer(DefaultBodyLimit::max(max_body_size))
+    }
+}
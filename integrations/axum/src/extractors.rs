@@ -1,14 +1,28 @@
-use axum::{extract::FromRequestParts, http::request::Parts};
-use std::future::Future;
+use axum::{
+    body::to_bytes,
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+use rspc_procedure::{ErrorCode, ProcedureError, ResolverError};
+use std::{error, fmt, future::Future};
 
 use std::marker::PhantomData;
 
+/// A function which creates the context for a request.
+///
+/// This is implemented for functions which take up to 16 Axum extractors and either return the context or a future resolving to `Result<TCtx, E>` where `E: Into<ProcedureError>`.
+/// If an extractor rejects the request or the function returns an error, it is sent to the client instead of executing the procedure.
 pub trait TCtxFunc<TCtx, TState, TMarker>: Clone + Send + Sync + 'static
 where
     TState: Send + Sync,
     TCtx: Send + 'static,
 {
-    fn exec(&self, parts: Parts, state: &TState) -> impl Future<Output = Result<TCtx, ()>> + Send;
+    fn exec(
+        &self,
+        parts: Parts,
+        state: &TState,
+    ) -> impl Future<Output = Result<TCtx, ProcedureError>> + Send;
 }
 
 pub struct ZeroArgMarker;
@@ -19,13 +33,57 @@ where
     TState: Send + Sync,
     TCtx: Send + 'static,
 {
-    async fn exec(&self, _: Parts, _: &TState) -> Result<TCtx, ()> {
+    async fn exec(&self, _: Parts, _: &TState) -> Result<TCtx, ProcedureError> {
         Ok(self.clone()())
     }
 }
 
+pub struct ZeroArgAsyncMarker;
+
+impl<TCtx, TFunc, TFut, TErr, TState> TCtxFunc<TCtx, TState, ZeroArgAsyncMarker> for TFunc
+where
+    TFunc: Fn() -> TFut + Clone + Send + Sync + 'static,
+    TFut: Future<Output = Result<TCtx, TErr>> + Send,
+    TErr: Into<ProcedureError>,
+    TState: Send + Sync,
+    TCtx: Send + 'static,
+{
+    async fn exec(&self, _: Parts, _: &TState) -> Result<TCtx, ProcedureError> {
+        self.clone()().await.map_err(Into::into)
+    }
+}
+
+/// Convert the rejection of an extractor into an error which can be sent to the client.
+///
+/// The status code of the rejection is kept and it's body is used as the message.
+async fn rejection_error(resp: Response) -> ProcedureError {
+    let code = ErrorCode::from_status_code(resp.status().as_u16())
+        .unwrap_or(ErrorCode::InternalServerError);
+    let message = to_bytes(resp.into_body(), MAX_REJECTION_SIZE)
+        .await
+        .map(|body| String::from_utf8_lossy(&body).into_owned())
+        .unwrap_or_default();
+
+    ResolverError::new(message.clone(), Some(RejectionError(message)))
+        .with_code(code)
+        .into()
+}
+
+const MAX_REJECTION_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+struct RejectionError(String);
+
+impl fmt::Display for RejectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl error::Error for RejectionError {}
+
 macro_rules! impl_fn {
-    ($marker:ident; $($generics:ident),*) => {
+    ($marker:ident, $async_marker:ident; $($generics:ident),*) => {
     		#[allow(unused_parens)]
         pub struct $marker<$($generics),*>(PhantomData<($($generics),*)>);
 
@@ -35,34 +93,66 @@ macro_rules! impl_fn {
             TState: Send + Sync,
             TCtx: Send + 'static
         {
-            async fn exec(&self, mut parts: Parts, state: &TState) -> Result<TCtx, ()>
+            async fn exec(&self, mut parts: Parts, state: &TState) -> Result<TCtx, ProcedureError>
             {
 		            $(
 										#[allow(non_snake_case)]
-										let Ok($generics) = $generics::from_request_parts(&mut parts, &state).await else {
-												 return Err(())
+										let $generics = match $generics::from_request_parts(&mut parts, &state)
+											.await
+											.map_err(IntoResponse::into_response)
+										{
+												Ok(v) => v,
+												Err(resp) => return Err(rejection_error(resp).await),
 										};
 								)*
 
                 Ok(self.clone()($($generics),*))
             }
+        }
+
+    		#[allow(unused_parens)]
+        pub struct $async_marker<$($generics),*>(PhantomData<($($generics),*)>);
+
+        impl<TCtx, TFunc, TFut, TErr, TState, $($generics: FromRequestParts<TState> + Send),*> TCtxFunc<TCtx, TState, $async_marker<$($generics),*>> for TFunc
+        where
+            TFunc: Fn($($generics),*) -> TFut + Clone + Send + Sync + 'static,
+            TFut: Future<Output = Result<TCtx, TErr>> + Send,
+            TErr: Into<ProcedureError>,
+            TState: Send + Sync,
+            TCtx: Send + 'static
+        {
+            async fn exec(&self, mut parts: Parts, state: &TState) -> Result<TCtx, ProcedureError>
+            {
+		            $(
+										#[allow(non_snake_case)]
+										let $generics = match $generics::from_request_parts(&mut parts, &state)
+											.await
+											.map_err(IntoResponse::into_response)
+										{
+												Ok(v) => v,
+												Err(resp) => return Err(rejection_error(resp).await),
+										};
+								)*
+
+                self.clone()($($generics),*).await.map_err(Into::into)
+            }
         }
     };
 }
 
-impl_fn!(OneArgMarker; T1);
-impl_fn!(TwoArgMarker; T1, T2);
-impl_fn!(ThreeArgMarker; T1, T2, T3);
-impl_fn!(FourArgMarker; T1, T2, T3, T4);
-impl_fn!(FiveArgMarker; T1, T2, T3, T4, T5);
-impl_fn!(SixArgMarker; T1, T2, T3, T4, T5, T6);
-impl_fn!(SevenArgMarker; T1, T2, T3, T4, T5, T6, T7);
-impl_fn!(EightArgMarker; T1, T2, T3, T4, T5, T6, T7, T8);
-impl_fn!(NineArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9);
-impl_fn!(TenArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
-impl_fn!(ElevenArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
-impl_fn!(TwelveArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
-impl_fn!(ThirteenArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
-impl_fn!(FourteenArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
-impl_fn!(FifteenArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
-impl_fn!(SixteenArgMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
+impl_fn!(OneArgMarker, OneArgAsyncMarker; T1);
+impl_fn!(TwoArgMarker, TwoArgAsyncMarker; T1, T2);
+impl_fn!(ThreeArgMarker, ThreeArgAsyncMarker; T1, T2, T3);
+impl_fn!(FourArgMarker, FourArgAsyncMarker; T1, T2, T3, T4);
+impl_fn!(FiveArgMarker, FiveArgAsyncMarker; T1, T2, T3, T4, T5);
+impl_fn!(SixArgMarker, SixArgAsyncMarker; T1, T2, T3, T4, T5, T6);
+impl_fn!(SevenArgMarker, SevenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7);
+impl_fn!(EightArgMarker, EightArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8);
+impl_fn!(NineArgMarker, NineArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9);
+impl_fn!(TenArgMarker, TenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
+impl_fn!(ElevenArgMarker, ElevenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
+impl_fn!(TwelveArgMarker, TwelveArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_fn!(ThirteenArgMarker, ThirteenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_fn!(FourteenArgMarker, FourteenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_fn!(FifteenArgMarker, FifteenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_fn!(SixteenArgMarker, SixteenArgAsyncMarker; T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
@@ -54,9 +54,13 @@ pub enum ResponseInner {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct JsonRPCError {
+    // This is the HTTP status of the `ErrorCode` (not a standard JSON-RPC error code) as that is what the rspc client expects.
     pub code: i32,
     pub message: String,
     pub data: Option<Value>,
+    // This is the JSON-RPC code of the `ErrorCode`.
+    #[serde(rename = "jsonRpcCode")]
+    pub json_rpc_code: i32,
 }
 
 // TODO: BREAK
@@ -77,3 +81,13 @@ impl ProcedureKind {
         }
     }
 }
+
+impl From<rspc_procedure::ProcedureKind> for ProcedureKind {
+    fn from(kind: rspc_procedure::ProcedureKind) -> Self {
+        match kind {
+            rspc_procedure::ProcedureKind::Query => Self::Query,
+            rspc_procedure::ProcedureKind::Mutation => Self::Mutation,
+            rspc_procedure::ProcedureKind::Subscription => Self::Subscription,
+        }
+    }
+}
@@ -4,12 +4,11 @@ use std::{
     future::{poll_fn, Future},
 };
 
-use rspc_procedure::{ProcedureError, ProcedureStream, Procedures};
-use serde::Serialize;
+use rspc_procedure::{ErrorCode, LogMessage, Logger, ProcedureError, ProcedureStream, Procedures};
 use serde_json::Value;
 use tokio::sync::{broadcast, mpsc, oneshot, Mutex};
 
-use super::jsonrpc::{self, RequestId, RequestInner, ResponseInner};
+use super::jsonrpc::{self, ProcedureKind, RequestId, RequestInner, ResponseInner};
 
 pub enum SubscriptionMap<'a> {
     Ref(&'a mut HashMap<RequestId, oneshot::Sender<()>>),
@@ -136,9 +135,10 @@ pub async fn handle_json_rpc<TCtx>(
                 jsonrpc: "2.0",
                 id: req.id.clone(),
                 result: ResponseInner::Error(jsonrpc::JsonRPCError {
-                    code: 400,
+                    code: ErrorCode::BadRequest.status_code().into(),
                     message: "invalid JSON-RPC version".into(),
                     data: None,
+                    json_rpc_code: ErrorCode::BadRequest.json_rpc_code(),
                 }),
             })
             .await
@@ -146,22 +146,32 @@ pub async fn handle_json_rpc<TCtx>(
                 // #[cfg(feature = "tracing")]
                 // tracing::error!("Failed to send response: {}", _err);
             });
+        return;
     }
 
-    let (path, input, sub_id, is_subscription) = match req.inner {
-        RequestInner::Query { path, input } => (path, input, None, false),
-        RequestInner::Mutation { path, input } => (path, input, None, false),
-        RequestInner::Subscription { path, input } => (path, input.1, Some(input.0), true),
+    let (kind, path, input, sub_id) = match req.inner {
+        RequestInner::Query { path, input } => (ProcedureKind::Query, path, input, None),
+        RequestInner::Mutation { path, input } => (ProcedureKind::Mutation, path, input, None),
+        RequestInner::Subscription { path, input } => {
+            (ProcedureKind::Subscription, path, input.1, Some(input.0))
+        }
         RequestInner::SubscriptionStop { input } => {
             subscriptions.remove(&input).await;
             return;
         }
     };
+    let is_subscription = kind == ProcedureKind::Subscription;
 
     let result = match procedures.get(&Cow::Borrowed(&*path)) {
+        Some(procedure) if ProcedureKind::from(procedure.kind()) != kind => {
+            // #[cfg(feature = "tracing")]
+            // tracing::error!("Error executing operation: the requested operation '{path}' was called with the wrong method");
+            ResponseInner::Error(invalid_kind(kind, procedure.kind().into()))
+        }
         Some(procedure) => {
+            let logger = procedures.logger().clone();
             let mut stream = procedure.exec_with_deserializer(ctx, input.unwrap_or(Value::Null));
-            let first_value = next(&mut stream).await;
+            let first_value = next(&mut stream, &logger, &path).await;
 
             if !is_subscription {
                 first_value
@@ -182,9 +192,10 @@ pub async fn handle_json_rpc<TCtx>(
                             jsonrpc: "2.0",
                             id: req.id.clone(),
                             result: ResponseInner::Error(jsonrpc::JsonRPCError {
-                                code: 400,
+                                code: ErrorCode::BadRequest.status_code().into(),
                                 message: "unsupported metho".into(),
                                 data: None,
+                                json_rpc_code: ErrorCode::BadRequest.json_rpc_code(),
                             }),
                         })
                         .await
@@ -201,10 +212,11 @@ pub async fn handle_json_rpc<TCtx>(
                                 jsonrpc: "2.0",
                                 id: req.id.clone(),
                                 result: ResponseInner::Error(jsonrpc::JsonRPCError {
-                                    code: 400,
+                                    code: ErrorCode::BadRequest.status_code().into(),
                                     message: "error creating subscription with null request id"
                                         .into(),
                                     data: None,
+                                    json_rpc_code: ErrorCode::BadRequest.json_rpc_code(),
                                 }),
                             })
                             .await
@@ -218,9 +230,10 @@ pub async fn handle_json_rpc<TCtx>(
                                 jsonrpc: "2.0",
                                 id: req.id.clone(),
                                 result: ResponseInner::Error(jsonrpc::JsonRPCError {
-                                    code: 400,
+                                    code: ErrorCode::BadRequest.status_code().into(),
                                     message: "error creating subscription with duplicate id".into(),
                                     data: None,
+                                    json_rpc_code: ErrorCode::BadRequest.json_rpc_code(),
                                 }),
                             })
                             .await
@@ -233,6 +246,7 @@ pub async fn handle_json_rpc<TCtx>(
                     let (shutdown_tx, mut shutdown_rx) = oneshot::channel();
                     subscriptions.insert(id.clone(), shutdown_tx).await;
                     let mut sender2 = sender.sender2();
+                    let path = path.clone();
                     tokio::spawn(async move {
                         match first_value {
                             Some(Ok(v)) => {
@@ -261,9 +275,12 @@ pub async fn handle_json_rpc<TCtx>(
                                 _ = &mut shutdown_rx => {
                                     // #[cfg(feature = "tracing")]
                                     // tracing::debug!("Removing subscription with id '{:?}'", id);
+
+                                    // This is triggered when the client unsubscribes or the socket is closed.
+                                    stream.close_and_drain().await;
                                     break;
                                 }
-                                v = next(&mut stream) => {
+                                v = next(&mut stream, &logger, &path) => {
                                     match v {
                                         Some(Ok(v)) => {
                                             let _ = sender2.send(jsonrpc::Response {
@@ -298,9 +315,10 @@ pub async fn handle_json_rpc<TCtx>(
             // #[cfg(feature = "tracing")]
             // tracing::error!("Error executing operation: the requested operation '{path}' is not supported by this server");
             ResponseInner::Error(jsonrpc::JsonRPCError {
-                code: 404,
+                code: ErrorCode::NotFound.status_code().into(),
                 message: "the requested operation is not supported by this server".to_string(),
                 data: None,
+                json_rpc_code: ErrorCode::NotFound.json_rpc_code(),
             })
         }
     };
@@ -318,49 +336,92 @@ pub async fn handle_json_rpc<TCtx>(
         });
 }
 
-async fn next(
+/// The error returned when a procedure is executed as a different kind (Eg. a mutation is executed as a query).
+pub fn invalid_kind(requested: ProcedureKind, actual: ProcedureKind) -> jsonrpc::JsonRPCError {
+    jsonrpc::JsonRPCError {
+        code: ErrorCode::MethodNotSupported.status_code().into(),
+        message: format!(
+            "the requested operation is a {} but was called as a {}",
+            actual.to_str(),
+            requested.to_str()
+        ),
+        data: None,
+        json_rpc_code: ErrorCode::MethodNotSupported.json_rpc_code(),
+    }
+}
+
+/// Get the next value from a procedure, converting any error into a JSON-RPC error.
+pub async fn next(
     stream: &mut ProcedureStream,
+    logger: &Logger,
+    path: &str,
 ) -> Option<Result<serde_json::Value, jsonrpc::JsonRPCError>> {
     let fut = stream.next();
     let mut fut = std::pin::pin!(fut);
     poll_fn(|cx| fut.as_mut().poll(cx)).await.map(|v| {
-        v.map_err(|err| match &err {
-            ProcedureError::NotFound => unimplemented!(), // Isn't created by this executor
-            ProcedureError::Deserialize(_) => jsonrpc::JsonRPCError {
-                code: 400,
-                message: "error deserializing procedure arguments".to_string(),
+        v.and_then(|v| v.serialize_with(serde_json::value::Serializer))
+            .map_err(|err| procedure_error(err, logger, path))
+    })
+}
+
+/// Convert an error from a procedure or context function into a JSON-RPC error.
+pub fn procedure_error(err: ProcedureError, logger: &Logger, path: &str) -> jsonrpc::JsonRPCError {
+    match &err {
+        // These aren't created by this executor but a context function could return them.
+        ProcedureError::NotFound | ProcedureError::Downcast(_) => jsonrpc::JsonRPCError {
+            code: err.code().status_code().into(),
+            message: err.message().into_owned(),
+            data: None,
+            json_rpc_code: err.code().json_rpc_code(),
+        },
+        ProcedureError::Deserialize(_) => jsonrpc::JsonRPCError {
+            code: err.code().status_code().into(),
+            message: "error deserializing procedure arguments".to_string(),
+            // This is the `DeserializeError` type exported with the router's types.
+            data: serde_json::to_value(&err).ok(),
+            json_rpc_code: err.code().json_rpc_code(),
+        },
+        ProcedureError::Resolver(resolver_err) => {
+            let legacy_error = resolver_err
+                .error()
+                .and_then(|v| v.downcast_ref::<rspc_procedure::LegacyErrorInterop>())
+                .cloned();
+
+            jsonrpc::JsonRPCError {
+                code: err.code().status_code().into(),
+                message: legacy_error
+                    .map(|v| v.0.clone())
+                    .unwrap_or_else(|| err.message().into_owned()),
+                // This is the typed error value so the client can handle it.
+                data: serde_json::to_value(&err).ok(),
+                json_rpc_code: err.code().json_rpc_code(),
+            }
+        }
+        ProcedureError::Unwind(payload) => {
+            logger.log(LogMessage::Panic {
+                procedure: path,
+                payload: payload.as_ref(),
+            });
+
+            jsonrpc::JsonRPCError {
+                code: err.code().status_code().into(),
+                message: err.message().into_owned(),
                 data: None,
-            },
-            ProcedureError::Downcast(_) => unimplemented!(), // Isn't supported by this executor
-            ProcedureError::Resolver(resolver_err) => {
-                let legacy_error = resolver_err
-                    .error()
-                    .and_then(|v| v.downcast_ref::<rspc_procedure::LegacyErrorInterop>())
-                    .cloned();
+                json_rpc_code: err.code().json_rpc_code(),
+            }
+        }
+        ProcedureError::Serializer(serializer_err) => {
+            logger.log(LogMessage::Serializer {
+                procedure: path,
+                error: serializer_err,
+            });
 
-                jsonrpc::JsonRPCError {
-                    code: match err {
-                        ProcedureError::NotFound => 404,
-                        ProcedureError::Deserialize(_) => 400,
-                        ProcedureError::Downcast(_) => 400,
-                        ProcedureError::Resolver(_) => 500, // This is a breaking change. It previously came from the user.
-                        ProcedureError::Unwind(_) => 500,
-                    },
-                    message: legacy_error
-                        .map(|v| v.0.clone())
-                        // This probally isn't a great format but we are assuming your gonna use the new router with a new executor for typesafe errors.
-                        .unwrap_or_else(|| err.to_string()),
-                    data: None,
-                }
+            jsonrpc::JsonRPCError {
+                code: err.code().status_code().into(),
+                message: err.message().into_owned(),
+                data: None,
+                json_rpc_code: err.code().json_rpc_code(),
             }
-            ProcedureError::Unwind(err) => panic!("{err:?}"), // Restore previous behavior lol
-                                                              // ProcedureError::Serializer(err) => panic!("{err:?}"),
-        })
-        .and_then(|v| {
-            Ok(v.as_serialize()
-                .unwrap()
-                .serialize(serde_json::value::Serializer)
-                .expect("Error serialzing value")) // This panicking is bad but this is the old exectuor
-        })
-    })
+        }
+    }
 }
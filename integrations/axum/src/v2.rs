@@ -1,22 +1,31 @@
 use std::{borrow::Borrow, collections::HashMap};
 
 use axum::{
-    body::{to_bytes, Body},
-    extract::{Request, State},
-    http::{request::Parts, Method, Response, StatusCode},
-    response::IntoResponse,
-    routing::{on, MethodFilter},
-    RequestExt, Router,
+    body::{Body, Bytes},
+    extract::{FromRequest, Request},
+    http::{header, request::Parts, HeaderMap, Method, Response, StatusCode},
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Sse,
+    },
+    Router,
 };
-use rspc_procedure::{Procedure, Procedures};
+use futures::{stream, StreamExt};
+use rspc_procedure::{ErrorCode, ProcedureStream, Procedures};
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json::Value;
 
 use crate::{
+    endpoint::HttpOptions,
     extractors::TCtxFunc,
     jsonrpc::{self, ProcedureKind, RequestId},
-    jsonrpc_exec::{handle_json_rpc, Sender, SubscriptionMap},
+    jsonrpc_exec::{handle_json_rpc, next, procedure_error, Sender, SubscriptionMap},
+    Endpoint,
 };
 
+/// Construct a new [`axum::Router`](axum::Router) with all features enabled.
+///
+/// Refer to [`Endpoint`] to configure which features are enabled.
 pub fn endpoint<TCtx, TCtxFnMarker, TCtxFn, S>(
     procedures: impl Borrow<Procedures<TCtx>>,
     ctx_fn: TCtxFn,
@@ -27,126 +36,496 @@ where
     TCtxFnMarker: Send + Sync + 'static,
     TCtxFn: TCtxFunc<TCtx, S, TCtxFnMarker>,
 {
-    let procedures = procedures.borrow().clone();
-
-    Router::<S>::new().route(
-        "/:id",
-        on(
-            MethodFilter::GET.or(MethodFilter::POST),
-            move |state: State<S>, req: axum::extract::Request<Body>| {
-                let procedures = procedures.clone();
-
-                async move {
-                    match (req.method(), &req.uri().path()[1..]) {
-                        (&Method::GET, "ws") => {
-                            #[cfg(feature = "ws")]
-                            {
-                                let mut req = req;
-                                return req
-                                    .extract_parts::<axum::extract::ws::WebSocketUpgrade>()
-                                    .await
-                                    .unwrap() // TODO: error handling
-                                    .on_upgrade(|socket| {
-                                        handle_websocket(
-                                            ctx_fn,
-                                            socket,
-                                            req.into_parts().0,
-                                            procedures,
-                                            state.0,
-                                        )
-                                    })
-                                    .into_response();
-                            }
+    Endpoint::new(procedures, ctx_fn)
+}
 
-                            #[cfg(not(feature = "ws"))]
-                            Response::builder()
-                                .status(StatusCode::NOT_FOUND)
-                                .body(Body::from("[]")) // TODO: Better error message which frontend is actually setup to handle.
-                                .unwrap()
-                        }
-                        (&Method::GET, _) => {
-                            handle_http(ctx_fn, ProcedureKind::Query, req, &procedures, state.0)
-                                .await
-                                .into_response()
-                        }
-                        (&Method::POST, _) => {
-                            handle_http(ctx_fn, ProcedureKind::Mutation, req, &procedures, state.0)
-                                .await
-                                .into_response()
-                        }
-                        _ => unreachable!(),
-                    }
+/// Execute the procedure at `/{id}` using Server-Sent Events or a regular HTTP request depending on what is enabled.
+pub(crate) async fn handle_procedure<TCtx, TCtxFn, TCtxFnMarker, TState>(
+    ctx_fn: TCtxFn,
+    req: Request,
+    procedures: &Procedures<TCtx>,
+    options: &HttpOptions,
+    state: TState,
+) -> Response<Body>
+where
+    TCtx: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TState, TCtxFnMarker>,
+    TState: Send + Sync + 'static,
+{
+    if options.sse && req.method() == Method::GET && accepts_event_stream(req.headers()) {
+        return handle_sse(ctx_fn, req, procedures, options, state).await;
+    }
+
+    if !options.endpoints {
+        return error_response(ErrorCode::NotFound, NOT_FOUND_MESSAGE);
+    }
+
+    handle_http(ctx_fn, req, procedures, options, state).await
+}
+
+/// How many requests within a batch are executed at once.
+const BATCH_CONCURRENCY: usize = 16;
+
+/// Execute a JSON array of queries and mutations concurrently.
+///
+/// The context function is run once for each request. The response is an array of results in the same order as the requests.
+pub(crate) async fn handle_batch<TCtx, TCtxFn, TCtxFnMarker, TState>(
+    ctx_fn: TCtxFn,
+    req: Request,
+    procedures: &Procedures<TCtx>,
+    options: &HttpOptions,
+    state: TState,
+) -> Response<Body>
+where
+    TCtx: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TState, TCtxFnMarker>,
+    TState: Send + Sync + 'static,
+{
+    let (parts, body) = match read_json_body(req).await {
+        Ok(v) => v,
+        Err((code, message)) => return error_response(code, message),
+    };
+    let requests =
+        parse_json::<Vec<jsonrpc::Request>>(&body, options.max_json_depth + MAX_ENVELOPE_DEPTH)
+            .and_then(|requests| {
+                requests
+                    .iter()
+                    .try_for_each(|req| check_input_depth(req, options.max_json_depth))
+                    .map(|_| requests)
+            });
+    let requests = match requests {
+        Ok(requests) => requests,
+        Err(err) => {
+            return error_response(ErrorCode::BadRequest, format!("error parsing batch: {err}"))
+        }
+    };
+
+    if requests.len() > options.max_batch_size {
+        return error_response(
+            ErrorCode::PayloadTooLarge,
+            format!(
+                "the batch contains {} requests but the maximum is {}",
+                requests.len(),
+                options.max_batch_size
+            ),
+        );
+    }
+
+    let responses = stream::iter(requests.into_iter().map(|req| {
+        let parts = parts.clone();
+        let ctx_fn = ctx_fn.clone();
+        let state = &state;
+        async move {
+            let error = |err: jsonrpc::JsonRPCError| jsonrpc::Response {
+                jsonrpc: "2.0",
+                id: req.id.clone(),
+                result: jsonrpc::ResponseInner::Error(err),
+            };
+
+            let path = match &req.inner {
+                jsonrpc::RequestInner::Query { path, .. }
+                | jsonrpc::RequestInner::Mutation { path, .. } => path,
+                jsonrpc::RequestInner::Subscription { .. }
+                | jsonrpc::RequestInner::SubscriptionStop { .. } => {
+                    return error(jsonrpc::JsonRPCError {
+                        code: ErrorCode::MethodNotSupported.status_code().into(),
+                        message: "subscriptions are not supported in a batch".into(),
+                        data: None,
+                        json_rpc_code: ErrorCode::MethodNotSupported.json_rpc_code(),
+                    });
                 }
-            },
-        ),
+            };
+
+            let ctx = match ctx_fn.exec(parts, state).await {
+                Ok(ctx) => ctx,
+                Err(err) => {
+                    // #[cfg(feature = "tracing")]
+                    // tracing::error!("Error executing context function: {:?}", err);
+
+                    return error(procedure_error(err, procedures.logger(), path));
+                }
+            };
+
+            let mut resp = Sender::Response(None);
+            handle_json_rpc(ctx, req, procedures, &mut resp, &mut SubscriptionMap::None).await;
+            match resp {
+                Sender::Response(Some(resp)) => resp,
+                _ => unreachable!("queries and mutations always respond"),
+            }
+        }
+    }))
+    .buffered(BATCH_CONCURRENCY)
+    .collect::<Vec<_>>()
+    .await;
+
+    json_response(StatusCode::OK, &responses)
+}
+
+/// Execute a query or subscription, sending each value as a [Server-Sent Event](https://developer.mozilla.org/en-US/docs/Web/API/Server-sent_events).
+///
+/// This is used for `GET` requests which accept `text/event-stream` so subscriptions work without a websocket (Eg. behind a proxy which blocks them).
+///
+/// Each value is sent as a `value` event and each error as an `error` event containing the JSON-RPC error.
+/// Once the stream has ended a `complete` event is sent so the client knows not to reconnect.
+async fn handle_sse<TCtx, TCtxFn, TCtxFnMarker, TState>(
+    ctx_fn: TCtxFn,
+    req: Request,
+    procedures: &Procedures<TCtx>,
+    options: &HttpOptions,
+    state: TState,
+) -> Response<Body>
+where
+    TCtx: Send + Sync + 'static,
+    TCtxFn: TCtxFunc<TCtx, TState, TCtxFnMarker>,
+    TState: Send + Sync + 'static,
+{
+    let (parts, _) = req.into_parts();
+    let path = parts.uri.path()[1..].to_string();
+
+    let Some(procedure) = procedures.get(path.as_str()) else {
+        return error_response(ErrorCode::NotFound, NOT_FOUND_MESSAGE);
+    };
+
+    if procedure.kind() == rspc_procedure::ProcedureKind::Mutation {
+        return error_response(
+            ErrorCode::MethodNotSupported,
+            "the requested operation is a mutation which can't be called with Server-Sent Events",
+        );
+    }
+
+    let input = match query_input(&parts, options) {
+        Ok(input) => input,
+        Err((code, message)) => return error_response(code, message),
+    };
+
+    let logger = procedures.logger().clone();
+    let ctx = match ctx_fn.exec(parts, &state).await {
+        Ok(ctx) => ctx,
+        Err(err) => {
+            // #[cfg(feature = "tracing")]
+            // tracing::error!("Error executing context function: {:?}", err);
+
+            return json_rpc_error_response(procedure_error(err, &logger, &path));
+        }
+    };
+
+    let stream = CloseOnDrop(Some(
+        procedure.exec_with_deserializer(ctx, input.unwrap_or(Value::Null)),
+    ));
+    let events = futures::stream::unfold(Some((stream, logger, path)), |state| async move {
+        let (mut stream, logger, path) = state?;
+        let inner = stream
+            .0
+            .as_mut()
+            .expect("only taken once the stream has ended");
+
+        let event = match next(inner, &logger, &path).await {
+            Some(Ok(v)) => Event::default().event("value").json_data(v),
+            Some(Err(err)) => Event::default().event("error").json_data(err),
+            None => {
+                stream.0 = None;
+                // `EventSource` ignores events without any data.
+                let event = Event::default().event("complete").json_data(Value::Null);
+                return Some((event, None));
+            }
+        };
+
+        Some((event, Some((stream, logger, path))))
+    });
+
+    Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+/// Closes a [`ProcedureStream`] if it's dropped before ending (Eg. the client disconnected from a Server-Sent Events response).
+struct CloseOnDrop(Option<ProcedureStream>);
+
+impl Drop for CloseOnDrop {
+    fn drop(&mut self) {
+        let Some(mut stream) = self.0.take() else {
+            return;
+        };
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async move { stream.close_and_drain().await });
+        }
+    }
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter_map(|v| v.trim().parse::<mime::Mime>().ok())
+        .any(|mime| mime.essence_str() == mime::TEXT_EVENT_STREAM.essence_str())
+}
+
+fn json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
+        return false;
+    };
+
+    let Ok(content_type) = content_type.to_str() else {
+        return false;
+    };
+
+    let Ok(mime) = content_type.parse::<mime::Mime>() else {
+        return false;
+    };
+
+    mime.type_() == "application"
+        && (mime.subtype() == "json" || mime.suffix().is_some_and(|name| name == "json"))
+}
+
+/// The most arrays and objects the JSON-RPC format nests the input within (Eg. `[{ "params": { "input": [id, ..] } }]` for a subscription in a batch).
+///
+/// This only bounds the depth of the whole message. The depth of each input is checked exactly with [`check_input_depth`].
+const MAX_ENVELOPE_DEPTH: usize = 4;
+
+/// Reject a request if it's input is nested deeper than `max_depth`.
+fn check_input_depth(req: &jsonrpc::Request, max_depth: usize) -> Result<(), String> {
+    let input = match &req.inner {
+        jsonrpc::RequestInner::Query { input, .. }
+        | jsonrpc::RequestInner::Mutation { input, .. }
+        | jsonrpc::RequestInner::Subscription {
+            input: (_, input), ..
+        } => input.as_ref(),
+        jsonrpc::RequestInner::SubscriptionStop { .. } => None,
+    };
+
+    if input.map(json_depth).unwrap_or_default() > max_depth {
+        return Err(format!("exceeds the maximum nesting depth of {max_depth}"));
+    }
+
+    Ok(())
+}
+
+fn json_depth(value: &Value) -> usize {
+    match value {
+        Value::Array(v) => 1 + v.iter().map(json_depth).max().unwrap_or_default(),
+        Value::Object(v) => 1 + v.values().map(json_depth).max().unwrap_or_default(),
+        _ => 0,
+    }
+}
+
+/// Deserialize JSON, rejecting it if arrays and objects are nested deeper than `max_depth`.
+///
+/// The depth is checked before deserializing so deeply nested JSON is rejected without recursing through it.
+fn parse_json<T: DeserializeOwned>(json: &[u8], max_depth: usize) -> Result<T, String> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for &b in json {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match b {
+            b'"' => in_string = true,
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(format!("exceeds the maximum nesting depth of {max_depth}"));
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    serde_json::from_slice(json).map_err(|err| err.to_string())
+}
+
+/// An error which rejects a request before the procedure is executed.
+type RequestError = (ErrorCode, String);
+
+/// Get the `input` of a `GET` request from it's query string.
+fn query_input(parts: &Parts, options: &HttpOptions) -> Result<Option<Value>, RequestError> {
+    let Some(query) = parts.uri.query() else {
+        return Ok(None);
+    };
+
+    if query.len() > options.max_query_size {
+        return Err((
+            ErrorCode::PayloadTooLarge,
+            format!(
+                "query string exceeds the maximum size of {} bytes",
+                options.max_query_size
+            ),
+        ));
+    }
+
+    form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == "input")
+        .map(|(_, input)| {
+            parse_json(input.as_bytes(), options.max_json_depth)
+                .map_err(|err| (ErrorCode::BadRequest, format!("error parsing input: {err}")))
+        })
+        .transpose()
+}
+
+/// Get the `input` of a request from it's body.
+fn body_input(body: &[u8], options: &HttpOptions) -> Result<Option<Value>, RequestError> {
+    if body.is_empty() {
+        return Ok(None);
+    }
+
+    parse_json(body, options.max_json_depth)
+        .map(Some)
+        .map_err(|err| (ErrorCode::BadRequest, format!("error parsing input: {err}")))
+}
+
+/// Read the body of a request, respecting the limit set by [`DefaultBodyLimit`](axum::extract::DefaultBodyLimit).
+///
+/// The body must have a JSON `Content-Type`. Browsers don't allow a cross-origin form or `fetch` to set this without a CORS preflight so it prevents CSRF.
+async fn read_json_body(req: Request) -> Result<(Parts, Bytes), RequestError> {
+    if !json_content_type(req.headers()) {
+        return Err((
+            ErrorCode::UnsupportedMediaType,
+            "the request body must have a JSON `Content-Type` header (Eg. `application/json`)"
+                .into(),
+        ));
+    }
+
+    let (parts, body) = req.into_parts();
+    Bytes::from_request(Request::from_parts(parts.clone(), body), &())
+        .await
+        .map(|body| (parts, body))
+        .map_err(|rejection| {
+            (
+                ErrorCode::from_status_code(rejection.status().as_u16())
+                    .unwrap_or(ErrorCode::BadRequest),
+                rejection.body_text(),
+            )
+        })
+}
+
+const NOT_FOUND_MESSAGE: &str = "the requested operation is not supported by this server";
+
+/// A JSON-RPC error response which isn't associated with a request id.
+fn error_response(code: ErrorCode, message: impl Into<String>) -> Response<Body> {
+    json_rpc_error_response(jsonrpc::JsonRPCError {
+        code: code.status_code().into(),
+        message: message.into(),
+        data: None,
+        json_rpc_code: code.json_rpc_code(),
+    })
+}
+
+/// A JSON-RPC error response which isn't associated with a request id. The HTTP status is the error's code.
+fn json_rpc_error_response(err: jsonrpc::JsonRPCError) -> Response<Body> {
+    json_response(
+        u16::try_from(err.code)
+            .ok()
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+        &jsonrpc::Response {
+            jsonrpc: "2.0",
+            id: RequestId::Null,
+            result: jsonrpc::ResponseInner::Error(err),
+        },
     )
 }
 
+fn json_response(status: StatusCode, body: &impl Serialize) -> Response<Body> {
+    match serde_json::to_vec(body) {
+        Ok(body) => (status, [(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(_err) => {
+            // #[cfg(feature = "tracing")]
+            // tracing::error!("Error serializing response: {}", _err);
+
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                [(header::CONTENT_TYPE, "application/json")],
+                r#"{"jsonrpc":"2.0","id":null,"result":{"type":"error","data":{"code":500,"message":"error serializing response","data":null,"jsonRpcCode":-32603}}}"#,
+            )
+                .into_response()
+        }
+    }
+}
+
 async fn handle_http<TCtx, TCtxFn, TCtxFnMarker, TState>(
     ctx_fn: TCtxFn,
-    kind: ProcedureKind,
     req: Request,
     procedures: &Procedures<TCtx>,
+    options: &HttpOptions,
     state: TState,
-) -> impl IntoResponse
+) -> Response<Body>
 where
     TCtx: Send + Sync + 'static,
     TCtxFn: TCtxFunc<TCtx, TState, TCtxFnMarker>,
     TState: Send + Sync + 'static,
 {
     let procedure_name = req.uri().path()[1..].to_string(); // Has to be allocated because `TCtxFn` takes ownership of `req`
-    let (parts, body) = req.into_parts();
-    let input = match parts.method {
-        Method::GET => parts
-            .uri
-            .query()
-            .map(|query| form_urlencoded::parse(query.as_bytes()))
-            .and_then(|mut params| params.find(|e| e.0 == "input").map(|e| e.1))
-            .map(|v| serde_json::from_str(&v))
-            .unwrap_or(Ok(None as Option<Value>)),
-        Method::POST => {
-            // TODO: Limit body size?
-            let body = to_bytes(body, usize::MAX).await.unwrap(); // TODO: error handling
-            (!body.is_empty())
-                .then(|| serde_json::from_slice(body.to_vec().as_slice()))
-                .unwrap_or(Ok(None))
+
+    let Some(procedure) = procedures.get(procedure_name.as_str()) else {
+        return error_response(ErrorCode::NotFound, NOT_FOUND_MESSAGE);
+    };
+
+    // Eg. reject a mutation called via `GET` or a subscription over HTTP.
+    let kind = ProcedureKind::from(procedure.kind());
+    if !options.methods(kind).contains(req.method()) {
+        // #[cfg(feature = "tracing")]
+        // tracing::error!("Attempted to execute a {kind:?} operation with {} over HTTP", req.method());
+
+        return error_response(
+            ErrorCode::MethodNotSupported,
+            format!(
+                "the requested operation is a {} which can't be called with {}",
+                kind.to_str(),
+                req.method()
+            ),
+        );
+    }
+
+    let (parts, input) = if req.method() == Method::GET {
+        let (parts, _) = req.into_parts();
+        let input = query_input(&parts, options);
+        (parts, input)
+    } else {
+        match read_json_body(req).await {
+            Ok((parts, body)) => (parts, body_input(&body, options)),
+            Err((code, message)) => return error_response(code, message),
         }
-        _ => unreachable!(),
     };
 
     let input = match input {
         Ok(input) => input,
-        Err(_err) => {
+        Err((code, message)) => {
             // #[cfg(feature = "tracing")]
-            // tracing::error!("Error passing parameters to operation '{procedure_name}': {_err}");
+            // tracing::error!("Error passing parameters to operation '{procedure_name}': {message}");
 
-            return Response::builder()
-                .status(StatusCode::NOT_FOUND)
-                .header("Content-Type", "application/json")
-                .body(Body::from(b"[]".as_slice()))
-                .unwrap();
+            return error_response(code, message);
         }
     };
 
     // #[cfg(feature = "tracing")]
     // tracing::debug!("Executing operation '{procedure_name}' with params {input:?}");
 
-    let mut resp = Sender::Response(None);
-
     let ctx = match ctx_fn.exec(parts, &state).await {
         Ok(ctx) => ctx,
-        Err(_err) => {
+        Err(err) => {
             // #[cfg(feature = "tracing")]
-            // tracing::error!("Error executing context function: {}", _err);
+            // tracing::error!("Error executing context function: {:?}", err);
 
-            return Response::builder()
-                .status(StatusCode::INTERNAL_SERVER_ERROR)
-                .header("Content-Type", "application/json")
-                .body(Body::from(b"[]".as_slice()))
-                .unwrap();
+            return json_rpc_error_response(procedure_error(
+                err,
+                procedures.logger(),
+                &procedure_name,
+            ));
         }
     };
 
+    let mut resp = Sender::Response(None);
     handle_json_rpc(
         ctx,
         jsonrpc::Request {
@@ -154,22 +533,15 @@ where
             id: RequestId::Null,
             inner: match kind {
                 ProcedureKind::Query => jsonrpc::RequestInner::Query {
-                    path: procedure_name.to_string(), // TODO: Lifetime instead of allocate?
+                    path: procedure_name,
                     input,
                 },
                 ProcedureKind::Mutation => jsonrpc::RequestInner::Mutation {
-                    path: procedure_name.to_string(), // TODO: Lifetime instead of allocate?
+                    path: procedure_name,
                     input,
                 },
                 ProcedureKind::Subscription => {
-                    // #[cfg(feature = "tracing")]
-                    // tracing::error!("Attempted to execute a subscription operation with HTTP");
-
-                    return Response::builder()
-                        .status(StatusCode::INTERNAL_SERVER_ERROR)
-                        .header("Content-Type", "application/json")
-                        .body(Body::from(b"[]".as_slice()))
-                        .unwrap();
+                    unreachable!("subscriptions can't be called with any HTTP method")
                 }
             },
         },
@@ -180,34 +552,19 @@ where
     .await;
 
     match resp {
-        Sender::Response(Some(resp)) => match serde_json::to_vec(&resp) {
-            Ok(v) => Response::builder()
-                .status(StatusCode::OK)
-                .header("Content-Type", "application/json")
-                .body(Body::from(v))
-                .unwrap(),
-            Err(_err) => {
-                // #[cfg(feature = "tracing")]
-                // tracing::error!("Error serializing response: {}", _err);
-
-                Response::builder()
-                    .status(StatusCode::INTERNAL_SERVER_ERROR)
-                    .header("Content-Type", "application/json")
-                    .body(Body::from(b"[]".as_slice()))
-                    .unwrap()
-            }
-        },
+        Sender::Response(Some(resp)) => json_response(StatusCode::OK, &resp),
         _ => unreachable!(),
     }
 }
 
 #[cfg(feature = "ws")]
-async fn handle_websocket<TCtx, TCtxFn, TCtxFnMarker, TState>(
+pub(crate) async fn handle_websocket<TCtx, TCtxFn, TCtxFnMarker, TState>(
     ctx_fn: TCtxFn,
     mut socket: axum::extract::ws::WebSocket,
     parts: Parts,
     procedures: Procedures<TCtx>,
     state: TState,
+    max_json_depth: usize,
 ) where
     TCtx: Send + Sync + 'static,
     TCtxFn: TCtxFunc<TCtx, TState, TCtxFnMarker>,
@@ -249,8 +606,8 @@ async fn handle_websocket<TCtx, TCtxFn, TCtxFnMarker, TState>(
                 match msg {
                     Some(Ok(msg)) => {
                        let res = match msg {
-                            Message::Text(text) => serde_json::from_str::<Value>(&text),
-                            Message::Binary(binary) => serde_json::from_slice(&binary),
+                            Message::Text(text) => parse_json::<Value>(text.as_bytes(), max_json_depth + MAX_ENVELOPE_DEPTH),
+                            Message::Binary(binary) => parse_json(&binary, max_json_depth + MAX_ENVELOPE_DEPTH),
                             Message::Ping(_) | Message::Pong(_) | Message::Close(_) => {
                                 continue;
                             }
@@ -259,17 +616,36 @@ async fn handle_websocket<TCtx, TCtxFn, TCtxFnMarker, TState>(
                         match res.and_then(|v| match v.is_array() {
                             true => serde_json::from_value::<Vec<jsonrpc::Request>>(v),
                             false => serde_json::from_value::<jsonrpc::Request>(v).map(|v| vec![v]),
+                        }.map_err(|err| err.to_string())).and_then(|reqs| {
+                            reqs.iter().try_for_each(|req| check_input_depth(req, max_json_depth)).map(|_| reqs)
                         }) {
                             Ok(reqs) => {
                                 for request in reqs {
+                                    // This doesn't need a context so a subscription can be stopped even if the context function would reject the request (Eg. an expired token).
+                                    if let jsonrpc::RequestInner::SubscriptionStop { input } = &request.inner {
+                                        SubscriptionMap::Ref(&mut subscriptions).remove(input).await;
+                                        continue;
+                                    }
+
                                     let ctx = match ctx_fn.exec(parts.clone(), &state).await {
                                         Ok(ctx) => {
                                             ctx
                                         },
-                                        Err(_err) => {
-
+                                        Err(err) => {
                                             // #[cfg(feature = "tracing")]
-                                            // tracing::error!("Error executing context function: {}", _err);
+                                            // tracing::error!("Error executing context function: {:?}", err);
+
+                                            let path = match &request.inner {
+                                                jsonrpc::RequestInner::Query { path, .. }
+                                                | jsonrpc::RequestInner::Mutation { path, .. }
+                                                | jsonrpc::RequestInner::Subscription { path, .. } => path.as_str(),
+                                                jsonrpc::RequestInner::SubscriptionStop { .. } => unreachable!("handled above"),
+                                            };
+                                            let _ = tx.send(jsonrpc::Response {
+                                                jsonrpc: "2.0",
+                                                id: request.id.clone(),
+                                                result: jsonrpc::ResponseInner::Error(procedure_error(err, procedures.logger(), path)),
+                                            }).await;
 
                                             continue;
                                         }
@@ -279,11 +655,20 @@ async fn handle_websocket<TCtx, TCtxFn, TCtxFnMarker, TState>(
                                     &mut SubscriptionMap::Ref(&mut subscriptions)).await;
                                 }
                             },
-                            Err(_err) => {
+                            Err(err) => {
                                 // #[cfg(feature = "tracing")]
-                                // tracing::error!("Error parsing websocket message: {}", _err);
-
-                                // TODO: Send report of error to frontend
+                                // tracing::error!("Error parsing websocket message: {}", err);
+
+                                let _ = tx.send(jsonrpc::Response {
+                                    jsonrpc: "2.0",
+                                    id: RequestId::Null,
+                                    result: jsonrpc::ResponseInner::Error(jsonrpc::JsonRPCError {
+                                        code: ErrorCode::BadRequest.status_code().into(),
+                                        message: format!("error parsing request: {err}"),
+                                        data: None,
+                                        json_rpc_code: ErrorCode::BadRequest.json_rpc_code(),
+                                    }),
+                                }).await;
 
                                 continue;
                             }
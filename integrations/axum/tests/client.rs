@@ -0,0 +1,64 @@
+mod common;
+
+use rspc::{Procedure, Router};
+use rspc_client::{Client, ProcedureKind};
+use tokio::net::TcpListener;
+
+use common::Error;
+
+// These are what `rspc::Rust` would generate for the router below.
+struct Procedures;
+
+struct Echo;
+
+impl rspc_client::Procedure for Echo {
+    type Input = String;
+    type Output = String;
+    type Error = ();
+    type Procedures = Procedures;
+    const KIND: ProcedureKind = ProcedureKind::Query;
+    const KEY: &'static str = "echo";
+}
+
+struct Double;
+
+impl rspc_client::Procedure for Double {
+    type Input = i32;
+    type Output = i32;
+    type Error = ();
+    type Procedures = Procedures;
+    const KIND: ProcedureKind = ProcedureKind::Mutation;
+    const KEY: &'static str = "double";
+}
+
+async fn client() -> Client<Procedures> {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "echo",
+            Procedure::builder().query(|_, input: String| async move { Ok::<_, Error>(input) }),
+        )
+        .procedure(
+            "double",
+            Procedure::builder().mutation(|_, input: i32| async move { Ok::<_, Error>(input * 2) }),
+        )
+        .build()
+        .unwrap();
+    let app = axum::Router::new().nest("/rspc", rspc_axum::endpoint(procedures, || ()));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    Client::new(format!("http://{addr}/rspc"))
+}
+
+#[tokio::test]
+async fn query_and_mutation() {
+    let client = client().await;
+
+    assert_eq!(
+        client.exec::<Echo>("hello".into()).await,
+        Ok("hello".into())
+    );
+    assert_eq!(client.exec::<Double>(21).await, Ok(42));
+}
@@ -0,0 +1,11 @@
+use rspc::ProcedureError;
+
+/// The error type for procedures which can't fail.
+#[derive(Debug, serde::Serialize, specta::Type)]
+pub enum Error {}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        match self {}
+    }
+}
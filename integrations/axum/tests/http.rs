@@ -0,0 +1,480 @@
+mod common;
+
+use axum::{
+    body::Body,
+    extract::Query,
+    http::{HeaderMap, Method, Request, StatusCode},
+};
+use http_body_util::BodyExt;
+use rspc::{ErrorCode, Procedure, ProcedureError, Procedures, ResolverError, Router};
+use rspc_axum::Endpoint;
+use serde_json::{json, Value};
+use tower::ServiceExt;
+
+use common::Error;
+
+#[derive(Debug, serde::Serialize)]
+enum AuthError {
+    MissingToken,
+}
+
+impl From<AuthError> for ProcedureError {
+    fn from(err: AuthError) -> Self {
+        ResolverError::new(err, None::<std::io::Error>)
+            .with_code(ErrorCode::Unauthorized)
+            .into()
+    }
+}
+
+fn procedures() -> Procedures<()> {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "echo",
+            Procedure::builder().query(|_, input: String| async move { Ok::<_, Error>(input) }),
+        )
+        .procedure(
+            "double",
+            Procedure::builder().mutation(|_, input: i32| async move { Ok::<_, Error>(input * 2) }),
+        )
+        .build()
+        .unwrap();
+    procedures
+}
+
+async fn send(req: Request<Body>) -> (StatusCode, Value) {
+    send_to(rspc_axum::endpoint(procedures(), || ()), req).await
+}
+
+async fn send_to(endpoint: axum::Router, req: Request<Body>) -> (StatusCode, Value) {
+    let app = axum::Router::new().nest("/rspc", endpoint);
+    let resp = app.oneshot(req).await.unwrap();
+    let status = resp.status();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn query() {
+    let (status, body) = send(
+        Request::get("/rspc/echo?input=%22hello%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        body,
+        json!({ "jsonrpc": "2.0", "id": null, "result": { "type": "response", "data": "hello" } })
+    );
+}
+
+#[tokio::test]
+async fn batch() {
+    let (status, body) = send(
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!([
+                    { "id": 1, "method": "query", "params": { "path": "echo", "input": "hello" } },
+                    { "id": 2, "method": "mutation", "params": { "path": "double", "input": 21 } },
+                    { "id": 3, "method": "query", "params": { "path": "unknown", "input": null } },
+                    { "id": 4, "method": "subscription", "params": { "path": "echo", "input": [4, "hello"] } },
+                    { "jsonrpc": "1.0", "id": 5, "method": "query", "params": { "path": "echo", "input": "hello" } },
+                ])
+                .to_string(),
+            ))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let body = body.as_array().unwrap();
+    assert_eq!(body.len(), 5);
+    assert_eq!(
+        body[0],
+        json!({ "jsonrpc": "2.0", "id": 1, "result": { "type": "response", "data": "hello" } })
+    );
+    assert_eq!(
+        body[1],
+        json!({ "jsonrpc": "2.0", "id": 2, "result": { "type": "response", "data": 42 } })
+    );
+    assert_eq!(body[2]["id"], 3);
+    assert_eq!(body[2]["result"]["type"], "error");
+    assert_eq!(
+        body[2]["result"]["data"]["code"],
+        ErrorCode::NotFound.status_code()
+    );
+    assert_eq!(body[3]["id"], 4);
+    assert_eq!(
+        body[3]["result"]["data"]["code"],
+        ErrorCode::MethodNotSupported.status_code()
+    );
+    // The procedure must not run for an unsupported JSON-RPC version.
+    assert_eq!(body[4]["id"], 5);
+    assert_eq!(body[4]["result"]["type"], "error");
+    assert_eq!(
+        body[4]["result"]["data"]["code"],
+        ErrorCode::BadRequest.status_code()
+    );
+}
+
+#[tokio::test]
+async fn batch_invalid_body() {
+    let (status, body) = send(
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from("{}"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["result"]["type"], "error");
+}
+
+#[tokio::test]
+async fn max_batch_size() {
+    let batch = |n: usize| {
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                Value::Array(
+                    (0..n)
+                        .map(|i| json!({ "id": i, "method": "query", "params": { "path": "echo", "input": i.to_string() } }))
+                        .collect(),
+                )
+                .to_string(),
+            ))
+            .unwrap()
+    };
+    let endpoint = || {
+        Endpoint::builder(procedures())
+            .with_batching()
+            .with_max_batch_size(32)
+            .build(|| ())
+    };
+
+    // The responses are in the same order as the requests even though they are executed concurrently.
+    let (status, body) = send_to(endpoint(), batch(32)).await;
+    assert_eq!(status, StatusCode::OK);
+    let body = body.as_array().unwrap();
+    assert_eq!(body.len(), 32);
+    for (i, resp) in body.iter().enumerate() {
+        assert_eq!(resp["id"], i);
+        assert_eq!(resp["result"]["data"], i.to_string());
+    }
+
+    let (status, body) = send_to(endpoint(), batch(33)).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["result"]["type"], "error");
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::PayloadTooLarge.status_code()
+    );
+}
+
+#[tokio::test]
+async fn builder_only_enables_configured_features() {
+    let endpoint = || Endpoint::builder(procedures()).with_batching().build(|| ());
+
+    let (status, _) = send_to(
+        endpoint(),
+        Request::get("/rspc/echo?input=%22hello%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send_to(
+        endpoint(),
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from("[]"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn query_methods() {
+    let endpoint = || {
+        Endpoint::builder(procedures())
+            .with_endpoints()
+            .with_query_methods([Method::POST])
+            .build(|| ())
+    };
+
+    let (status, body) = send_to(
+        endpoint(),
+        Request::post("/rspc/echo")
+            .header("content-type", "application/json")
+            .body(Body::from(r#""hello""#))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["data"], "hello");
+
+    let (status, body) = send_to(
+        endpoint(),
+        Request::get("/rspc/echo?input=%22hello%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::MethodNotSupported.status_code()
+    );
+}
+
+#[tokio::test]
+async fn max_body_size() {
+    let (status, body) = send_to(
+        Endpoint::builder(procedures())
+            .with_endpoints()
+            .with_max_body_size(4)
+            .build(|| ()),
+        Request::post("/rspc/double")
+            .header("content-type", "application/json")
+            .body(Body::from("123456"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::PayloadTooLarge.status_code()
+    );
+}
+
+#[tokio::test]
+async fn mutation_requires_json_content_type() {
+    let (status, body) = send(
+        Request::post("/rspc/double")
+            .header("content-type", "text/plain")
+            .body(Body::from("21"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::UnsupportedMediaType.status_code()
+    );
+
+    let (status, body) = send(
+        Request::post("/rspc/double")
+            .header("content-type", "application/json; charset=utf-8")
+            .body(Body::from("21"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["data"], 42);
+}
+
+#[tokio::test]
+async fn max_query_size() {
+    let (status, body) = send_to(
+        Endpoint::builder(procedures())
+            .with_endpoints()
+            .with_max_query_size(8)
+            .build(|| ()),
+        Request::get("/rspc/echo?input=%22hello%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::PayloadTooLarge.status_code()
+    );
+}
+
+#[tokio::test]
+async fn max_json_depth() {
+    let endpoint = || {
+        Endpoint::builder(procedures())
+            .with_endpoints()
+            .with_batching()
+            .with_max_json_depth(2)
+            .build(|| ())
+    };
+
+    // Brackets within a string aren't counted.
+    let (status, body) = send_to(
+        endpoint(),
+        Request::get("/rspc/echo?input=%22%5B%5B%5B%5C%22%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["data"], "[[[\"");
+
+    let (status, body) = send_to(
+        endpoint(),
+        Request::post("/rspc/double")
+            .header("content-type", "application/json")
+            .body(Body::from("[[[1]]]"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::BadRequest.status_code()
+    );
+
+    let (status, _) = send_to(
+        endpoint(),
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!([{ "id": 1, "method": "mutation", "params": { "path": "double", "input": [[[1]]] } }])
+                    .to_string(),
+            ))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn context_function_error() {
+    let endpoint = || {
+        rspc_axum::endpoint(procedures(), |headers: HeaderMap| async move {
+            match headers.contains_key("authorization") {
+                true => Ok(()),
+                false => Err(AuthError::MissingToken),
+            }
+        })
+    };
+
+    let (status, body) = send_to(
+        endpoint(),
+        Request::get("/rspc/echo?input=%22hello%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["result"]["type"], "error");
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::Unauthorized.status_code()
+    );
+    assert_eq!(body["result"]["data"]["data"], "MissingToken");
+
+    let (status, _) = send_to(
+        endpoint(),
+        Request::get("/rspc/echo?input=%22hello%22")
+            .header("authorization", "token")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = send_to(
+        endpoint(),
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!([{ "id": 1, "method": "query", "params": { "path": "echo", "input": "hello" } }])
+                    .to_string(),
+            ))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["id"], 1);
+    assert_eq!(
+        body[0]["result"]["data"]["code"],
+        ErrorCode::Unauthorized.status_code()
+    );
+    assert_eq!(body[0]["result"]["data"]["data"], "MissingToken");
+}
+
+#[tokio::test]
+async fn context_function_extractor_rejection() {
+    #[derive(serde::Deserialize)]
+    struct Params {
+        #[allow(dead_code)]
+        token: String,
+    }
+
+    let (status, body) = send_to(
+        rspc_axum::endpoint(procedures(), |_: Query<Params>| ()),
+        Request::get("/rspc/echo?input=%22hello%22")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["result"]["data"]["code"],
+        ErrorCode::BadRequest.status_code()
+    );
+    assert!(body["result"]["data"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("missing field `token`"));
+}
+
+#[tokio::test]
+async fn error_code() {
+    #[derive(Debug, serde::Serialize, specta::Type)]
+    struct Conflict;
+
+    impl rspc::Error for Conflict {
+        fn into_procedure_error(self) -> ProcedureError {
+            ResolverError::new(self, None::<std::io::Error>)
+                .with_code(ErrorCode::Conflict)
+                .into()
+        }
+    }
+
+    let endpoint = || {
+        let (procedures, _) = <Router>::new()
+            .procedure(
+                "conflict",
+                Procedure::builder().mutation(|_, _: ()| async { Err::<(), _>(Conflict) }),
+            )
+            .build()
+            .unwrap();
+        rspc_axum::endpoint(procedures, || ())
+    };
+
+    // The error sent to the client has the HTTP status and JSON-RPC code of the `ErrorCode`.
+    let (status, body) = send_to(
+        endpoint(),
+        Request::post("/rspc/conflict")
+            .header("content-type", "application/json")
+            .body(Body::from("null"))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["result"]["data"]["code"], 409);
+    assert_eq!(body["result"]["data"]["jsonRpcCode"], -32009);
+
+    let (status, body) = send_to(
+        endpoint(),
+        Request::post("/rspc/_batch")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!([{ "id": 1, "method": "mutation", "params": { "path": "conflict", "input": null } }])
+                    .to_string(),
+            ))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body[0]["result"]["data"]["code"], 409);
+    assert_eq!(body[0]["result"]["data"]["jsonRpcCode"], -32009);
+}
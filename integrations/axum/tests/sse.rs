@@ -0,0 +1,95 @@
+mod common;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+};
+use futures::StreamExt;
+use http_body_util::BodyExt;
+use rspc::{
+    middleware::{EndReason, Lifecycle},
+    Procedure, Router,
+};
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+
+use common::Error;
+
+fn app(on_end: mpsc::UnboundedSender<EndReason>) -> axum::Router {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "count",
+            Procedure::builder().subscription(|_, to: i32| async move {
+                Ok(futures::stream::iter((0..to).map(Ok::<_, Error>)))
+            }),
+        )
+        .procedure(
+            "forever",
+            Procedure::builder().subscription(move |_, _: ()| {
+                let on_end = on_end.clone();
+                async move {
+                    Ok(Lifecycle::new(
+                        futures::stream::iter([Ok::<_, Error>(1)])
+                            .chain(futures::stream::pending()),
+                    )
+                    .on_end(move |reason| async move {
+                        let _ = on_end.send(reason);
+                    }))
+                }
+            }),
+        )
+        .procedure(
+            "increment",
+            Procedure::builder().mutation(|_, input: i32| async move { Ok::<_, Error>(input + 1) }),
+        )
+        .build()
+        .unwrap();
+
+    axum::Router::new().nest("/rspc", rspc_axum::endpoint(procedures, || ()))
+}
+
+fn sse_request(uri: &str) -> Request<Body> {
+    Request::get(uri)
+        .header(header::ACCEPT, "text/event-stream")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn subscription() {
+    let resp = app(mpsc::unbounded_channel().0)
+        .oneshot(sse_request("/rspc/count?input=2"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(
+        std::str::from_utf8(&body).unwrap(),
+        "event: value\ndata: 0\n\nevent: value\ndata: 1\n\nevent: complete\ndata: null\n\n"
+    );
+}
+
+#[tokio::test]
+async fn mutation_is_rejected() {
+    let resp = app(mpsc::unbounded_channel().0)
+        .oneshot(sse_request("/rspc/increment?input=1"))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[tokio::test]
+async fn disconnect_closes_stream() {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let resp = app(tx).oneshot(sse_request("/rspc/forever")).await.unwrap();
+
+    let mut body = resp.into_body();
+    let frame = body.frame().await.unwrap().unwrap().into_data().unwrap();
+    assert_eq!(&frame[..], b"event: value\ndata: 1\n\n");
+
+    // The client disconnecting drops the response body.
+    drop(body);
+    assert_eq!(rx.recv().await, Some(EndReason::Unsubscribed));
+}
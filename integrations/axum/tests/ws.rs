@@ -0,0 +1,167 @@
+#![cfg(feature = "ws")]
+
+mod common;
+
+use futures::{SinkExt, StreamExt};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use rspc::{
+    middleware::{EndReason, Lifecycle},
+    ErrorCode, Procedure, ResolverError, Router,
+};
+use rspc_axum::Endpoint;
+use serde_json::{json, Value};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+    time::timeout,
+};
+use tokio_tungstenite::{tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+use common::Error;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn connect(app: axum::Router) -> Socket {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let (socket, _) = tokio_tungstenite::connect_async(format!("ws://{addr}/rspc/ws"))
+        .await
+        .unwrap();
+    socket
+}
+
+async fn send(socket: &mut Socket, msg: Value) -> Value {
+    socket.send(Message::text(msg.to_string())).await.unwrap();
+    match socket.next().await.unwrap().unwrap() {
+        Message::Text(text) => serde_json::from_str(&text).unwrap(),
+        msg => panic!("unexpected message {msg:?}"),
+    }
+}
+
+#[tokio::test]
+async fn max_json_depth() {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "echo",
+            Procedure::builder().query(|_, input: Value| async move { Ok::<_, Error>(input) }),
+        )
+        .procedure(
+            "once",
+            Procedure::builder().subscription(|_, input: Value| async move {
+                Ok(futures::stream::once(async move { Ok::<_, Error>(input) }))
+            }),
+        )
+        .build()
+        .unwrap();
+    let mut socket = connect(
+        axum::Router::new().nest(
+            "/rspc",
+            Endpoint::builder(procedures)
+                .with_websocket()
+                .with_max_json_depth(2)
+                .build(|| ()),
+        ),
+    )
+    .await;
+
+    // The depth of the input is the same whether or not the message is a batch.
+    for input in [json!([[1]]), json!([[[1]]])] {
+        let query =
+            json!({ "id": 1, "method": "query", "params": { "path": "echo", "input": input } });
+        let resp = send(&mut socket, query.clone()).await;
+        let batch_resp = send(&mut socket, json!([query])).await;
+        assert_eq!(resp, batch_resp);
+
+        if input == json!([[1]]) {
+            assert_eq!(resp["result"]["data"], input);
+        } else {
+            assert_eq!(resp["result"]["type"], "error");
+            assert_eq!(
+                resp["result"]["data"]["code"],
+                ErrorCode::BadRequest.status_code()
+            );
+        }
+    }
+
+    // A subscription's input is nested within `[id, input]`.
+    let resp = send(
+        &mut socket,
+        json!([{ "id": 2, "method": "subscription", "params": { "path": "once", "input": [2, [[1]]] } }]),
+    )
+    .await;
+    assert_eq!(resp["id"], 2);
+    assert_eq!(resp["result"]["type"], "event");
+    assert_eq!(resp["result"]["data"], json!([[1]]));
+}
+
+#[tokio::test]
+async fn subscription_stop_with_rejected_context() {
+    let (on_end, mut ended) = mpsc::unbounded_channel();
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "forever",
+            Procedure::builder().subscription(move |_, _: ()| {
+                let on_end = on_end.clone();
+                async move {
+                    Ok(Lifecycle::new(
+                        futures::stream::iter([Ok::<_, Error>(1)])
+                            .chain(futures::stream::pending()),
+                    )
+                    .on_end(move |reason| async move {
+                        let _ = on_end.send(reason);
+                    }))
+                }
+            }),
+        )
+        .build()
+        .unwrap();
+
+    // Eg. the user's token expires while the subscription is running.
+    let expired = Arc::new(AtomicBool::new(false));
+    let mut socket = connect(axum::Router::new().nest(
+        "/rspc",
+        rspc_axum::endpoint(procedures, {
+            let expired = expired.clone();
+            move || {
+                let expired = expired.load(Ordering::SeqCst);
+                async move {
+                    match expired {
+                        true => Err(ResolverError::new("expired", None::<std::io::Error>)
+                            .with_code(ErrorCode::Unauthorized)),
+                        false => Ok(()),
+                    }
+                }
+            }
+        }),
+    ))
+    .await;
+
+    let resp = send(
+        &mut socket,
+        json!({ "id": 1, "method": "subscription", "params": { "path": "forever", "input": [1, null] } }),
+    )
+    .await;
+    assert_eq!(resp["result"], json!({ "type": "event", "data": 1 }));
+
+    expired.store(true, Ordering::SeqCst);
+    socket
+        .send(Message::text(
+            json!({ "id": null, "method": "subscriptionStop", "params": { "input": 1 } })
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        timeout(Duration::from_secs(5), ended.recv()).await,
+        Ok(Some(EndReason::Unsubscribed))
+    );
+}
@@ -24,14 +24,16 @@
 use std::{
     borrow::Cow,
     collections::HashMap,
+    future::poll_fn,
     sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{ready, Poll},
 };
 
-use rspc_procedure::{ProcedureError, Procedures};
+use rspc_procedure::{LogMessage, ProcedureError, Procedures};
 use serde::{de::Error, Deserialize, Serialize};
 use serde_json::value::RawValue;
 use tauri::{
-    async_runtime::{spawn, JoinHandle},
+    async_runtime::{channel, spawn, Sender},
     generate_handler,
     ipc::{Channel, InvokeResponseBody, IpcResponse},
     plugin::{Builder, TauriPlugin},
@@ -39,7 +41,8 @@ use tauri::{
 };
 
 struct RpcHandler<R, TCtxFn, TCtx> {
-    subscriptions: Mutex<HashMap<u32, JoinHandle<()>>>,
+    // Dropping the `Sender` will close the subscription.
+    subscriptions: Mutex<HashMap<u32, Sender<()>>>,
     ctx_fn: TCtxFn,
     procedures: Procedures<TCtx>,
     phantom: std::marker::PhantomData<fn() -> R>,
@@ -51,7 +54,7 @@ where
     TCtxFn: Fn(tauri::Window<R>) -> TCtx + Send + Sync + 'static,
     TCtx: Send + 'static,
 {
-    fn subscriptions(&self) -> MutexGuard<HashMap<u32, JoinHandle<()>>> {
+    fn subscriptions(&self) -> MutexGuard<HashMap<u32, Sender<()>>> {
         self.subscriptions
             .lock()
             .unwrap_or_else(PoisonError::into_inner)
@@ -73,13 +76,7 @@ where
                     send(
                         &channel,
                         Response::Value {
-                            code: match err {
-                                ProcedureError::NotFound => 404,
-                                ProcedureError::Deserialize(_) => 400,
-                                ProcedureError::Downcast(_) => 400,
-                                ProcedureError::Resolver(_) => 500, // This is a breaking change. It previously came from the user.
-                                ProcedureError::Unwind(_) => 500,
-                            },
+                            code: err.code().status_code(),
                             value: &err,
                         },
                     );
@@ -92,46 +89,76 @@ where
                     None => procedure.exec_with_deserializer(ctx, serde_json::Value::Null),
                 };
 
+                let (close_tx, mut close_rx) = channel::<()>(1);
+
+                let logger = self.procedures.logger().clone();
                 let this = self.clone();
-                let handle = spawn(async move {
-                    while let Some(value) = stream.next().await {
-                        match value {
-                            Ok(v) => send(
+                spawn(async move {
+                    let closed = poll_fn(|cx| loop {
+                        if close_rx.poll_recv(cx).is_ready() {
+                            return Poll::Ready(true);
+                        }
+
+                        let Some(value) = ready!(stream.poll_next(cx)) else {
+                            return Poll::Ready(false);
+                        };
+
+                        match value.and_then(|v| v.serialize_with(serde_json::value::Serializer)) {
+                            Ok(value) => send(
                                 &channel,
                                 Response::Value {
                                     code: 200,
-                                    value: &v.as_serialize().unwrap(),
+                                    value: &value,
                                 },
                             ),
-                            Err(err) => send(
-                                &channel,
-                                Response::Value {
-                                    code: match err {
-                                        ProcedureError::NotFound => 404,
-                                        ProcedureError::Deserialize(_) => 400,
-                                        ProcedureError::Downcast(_) => 400,
-                                        ProcedureError::Resolver(_) => 500, // This is a breaking change. It previously came from the user.
-                                        ProcedureError::Unwind(_) => 500,
+                            Err(err) => {
+                                match &err {
+                                    ProcedureError::Serializer(error) => {
+                                        logger.log(LogMessage::Serializer {
+                                            procedure: &path,
+                                            error,
+                                        })
+                                    }
+                                    ProcedureError::Unwind(payload) => {
+                                        logger.log(LogMessage::Panic {
+                                            procedure: &path,
+                                            payload: payload.as_ref(),
+                                        })
+                                    }
+                                    _ => {}
+                                }
+
+                                send(
+                                    &channel,
+                                    Response::Value {
+                                        code: err.code().status_code(),
+                                        value: &err,
                                     },
-                                    value: &err,
-                                },
-                            ),
+                                )
+                            }
                         }
+                    })
+                    .await;
+                    if closed {
+                        stream.close_and_drain().await;
                     }
 
-                    this.subscriptions().remove(&id);
+                    // Closing our receiver lets us check the subscription hasn't been replaced by a new one with the same id.
+                    drop(close_rx);
+                    let mut subscriptions = this.subscriptions();
+                    if subscriptions.get(&id).is_some_and(Sender::is_closed) {
+                        subscriptions.remove(&id);
+                    }
+                    drop(subscriptions);
                     send::<()>(&channel, Response::Done);
                 });
 
-                // if the client uses an existing ID, we will assume the previous subscription is no longer required
-                if let Some(old) = self.subscriptions().insert(id, handle) {
-                    old.abort();
-                }
+                // if the client uses an existing ID, we will assume the previous subscription is no longer required.
+                // Dropping it's `Sender` will close it.
+                self.subscriptions().insert(id, close_tx);
             }
             Request::Abort(id) => {
-                if let Some(h) = self.subscriptions().remove(&id) {
-                    h.abort();
-                }
+                self.subscriptions().remove(&id);
             }
         }
     }
@@ -0,0 +1,179 @@
+use std::{any::type_name, error, fmt};
+
+use futures_util::{stream, Stream};
+use rspc_procedure::{DowncastError, DynInput, ProcedureError, ProcedureStream, Procedures};
+
+use crate::ProcedureKind;
+
+/// Call procedures in-process using typed values.
+///
+/// The input and output are passed through directly so there is no serialization round-trip.
+/// This is useful for background jobs and tests which need to call procedures without going over HTTP.
+///
+/// ```rust
+/// # use rspc::{Caller, Procedure, ProcedureError, ResolverError, Router};
+/// # #[derive(Debug, serde::Serialize, specta::Type)]
+/// # struct Error;
+/// # impl rspc::Error for Error {
+/// #     fn into_procedure_error(self) -> ProcedureError {
+/// #         ResolverError::new(self, None::<std::io::Error>).into()
+/// #     }
+/// # }
+/// let (procedures, _) = <Router>::new()
+///     .procedure(
+///         "double",
+///         Procedure::builder().query(|_, n: i32| async move { Ok::<_, Error>(n * 2) }),
+///     )
+///     .build()
+///     .unwrap();
+///
+/// let caller = Caller::new(procedures);
+/// # futures::executor::block_on(async {
+/// let result = caller.query::<i32, Error>("double", (), 21).await.unwrap();
+/// assert_eq!(result, 42);
+/// # });
+/// ```
+pub struct Caller<TCtx> {
+    procedures: Procedures<TCtx>,
+}
+
+impl<TCtx> Caller<TCtx> {
+    pub fn new(procedures: impl Into<Procedures<TCtx>>) -> Self {
+        Self {
+            procedures: procedures.into(),
+        }
+    }
+
+    /// Execute a query and return it's result.
+    pub async fn query<TOutput, TError>(
+        &self,
+        key: &str,
+        ctx: TCtx,
+        input: impl Send + 'static,
+    ) -> Result<TOutput, CallError<TError>>
+    where
+        TOutput: Send + 'static,
+        TError: 'static,
+    {
+        let mut stream = self.exec(ProcedureKind::Query, key, ctx, input)?;
+        next(&mut stream).await.unwrap_or(Err(CallError::Empty))
+    }
+
+    /// Execute a mutation and return it's result.
+    pub async fn mutation<TOutput, TError>(
+        &self,
+        key: &str,
+        ctx: TCtx,
+        input: impl Send + 'static,
+    ) -> Result<TOutput, CallError<TError>>
+    where
+        TOutput: Send + 'static,
+        TError: 'static,
+    {
+        let mut stream = self.exec(ProcedureKind::Mutation, key, ctx, input)?;
+        next(&mut stream).await.unwrap_or(Err(CallError::Empty))
+    }
+
+    /// Execute a subscription and return a stream of it's results.
+    pub fn subscription<TOutput, TError>(
+        &self,
+        key: &str,
+        ctx: TCtx,
+        input: impl Send + 'static,
+    ) -> impl Stream<Item = Result<TOutput, CallError<TError>>> + Send + 'static
+    where
+        TOutput: Send + 'static,
+        TError: Send + 'static,
+    {
+        let stream = self.exec(ProcedureKind::Subscription, key, ctx, input);
+        stream::unfold(Some(stream), |stream| async move {
+            match stream? {
+                Ok(mut stream) => {
+                    let value = next(&mut stream).await?;
+                    Some((value, Some(Ok(stream))))
+                }
+                Err(err) => Some((Err(err), None)),
+            }
+        })
+    }
+
+    fn exec<TError>(
+        &self,
+        kind: ProcedureKind,
+        key: &str,
+        ctx: TCtx,
+        input: impl Send + 'static,
+    ) -> Result<ProcedureStream, CallError<TError>> {
+        let procedure = self
+            .procedures
+            .get(key)
+            .filter(|procedure| procedure.kind() == kind.into())
+            .ok_or(CallError::Procedure(ProcedureError::NotFound))?;
+
+        let mut input = Some(input);
+        Ok(procedure.exec(ctx, DynInput::new_value(&mut input)))
+    }
+}
+
+async fn next<TOutput: Send + 'static, TError: 'static>(
+    stream: &mut ProcedureStream,
+) -> Option<Result<TOutput, CallError<TError>>> {
+    Some(match stream.next().await? {
+        Ok(output) => {
+            let from = output.type_name();
+            output.as_value().ok_or_else(|| {
+                CallError::Procedure(DowncastError::new(Some(from), type_name::<TOutput>()).into())
+            })
+        }
+        Err(ProcedureError::Resolver(err)) => Err(err
+            .downcast()
+            .map_or_else(|err| CallError::Procedure(err.into()), CallError::Resolver)),
+        Err(err) => Err(CallError::Procedure(err)),
+    })
+}
+
+impl<TCtx> Clone for Caller<TCtx> {
+    fn clone(&self) -> Self {
+        Self {
+            procedures: self.procedures.clone(),
+        }
+    }
+}
+
+impl<TCtx> fmt::Debug for Caller<TCtx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Caller")
+            .field("procedures", &self.procedures.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// An error returned from calling a procedure with [`Caller`].
+pub enum CallError<TError> {
+    /// The procedure returned it's typed error.
+    Resolver(TError),
+    /// The procedure failed for another reason.
+    ///
+    /// This includes the procedure not existing (or being a different kind), the input or output not matching the procedure's types, a panic or an error which is not a `TError`.
+    Procedure(ProcedureError),
+    /// The procedure finished without returning a value.
+    Empty,
+}
+
+impl<TError: fmt::Debug> fmt::Debug for CallError<TError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Resolver(err) => write!(f, "Resolver({err:?})"),
+            Self::Procedure(err) => write!(f, "Procedure({err:?})"),
+            Self::Empty => write!(f, "Empty"),
+        }
+    }
+}
+
+impl<TError: fmt::Debug> fmt::Display for CallError<TError> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl<TError: fmt::Debug> error::Error for CallError<TError> {}
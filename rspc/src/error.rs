@@ -1,7 +1,83 @@
 use rspc_procedure::ProcedureError;
-use specta::Type;
+use specta::{
+    datatype::{DataType, LiteralType},
+    Generics, Type, TypeCollection,
+};
 
+/// An error which can be returned from a procedure.
+///
+/// The [`ErrorCode`](crate::ErrorCode) of the error can be set with [`ResolverError::with_code`](crate::ResolverError::with_code).
+/// Integrations use this to determine the HTTP status or JSON-RPC error code to return.
+///
+/// ```rust
+/// use rspc::{ErrorCode, ProcedureError, ResolverError};
+///
+/// #[derive(Debug, serde::Serialize, specta::Type)]
+/// enum Error {
+///     Unauthorized,
+/// }
+///
+/// impl rspc::Error for Error {
+///     fn into_procedure_error(self) -> ProcedureError {
+///         let code = match self {
+///             Self::Unauthorized => ErrorCode::Unauthorized,
+///         };
+///
+///         ResolverError::new(self, None::<std::io::Error>)
+///             .with_code(code)
+///             .into()
+///     }
+/// }
+/// ```
 // TODO: Drop bounds on this cause they can be added at the impl.
 pub trait Error: Type + 'static {
     fn into_procedure_error(self) -> ProcedureError;
 }
+
+// The shape of a `ProcedureError::Deserialize` when it's sent to the client.
+// This is exported with the Typescript bindings so the client can show the error next to the field which caused it.
+
+/// The input to a procedure failed to deserialize.
+///
+/// `path` is the location of the field which caused the error (Eg. `["users", 0, "name"]`).
+#[derive(Type)]
+#[specta(rename = "DeserializeError")]
+#[allow(dead_code)]
+pub(crate) struct DeserializeErrorType {
+    #[specta(rename = "~rspc", type = RspcMarker)]
+    rspc: (),
+    #[specta(type = DeserializeVariant)]
+    variant: (),
+    message: String,
+    path: Vec<PathSegmentType>,
+    expected: Option<String>,
+    found: Option<String>,
+}
+
+// Refer to `rspc_procedure::PathSegment`.
+#[derive(Type)]
+#[specta(rename = "PathSegment", untagged)]
+#[allow(dead_code)]
+enum PathSegmentType {
+    Field(String),
+    Index(u32),
+}
+
+// The `"~rspc": true` field which identifies errors created by rspc.
+#[allow(dead_code)]
+struct RspcMarker;
+
+impl Type for RspcMarker {
+    fn inline(_: &mut TypeCollection, _: Generics) -> DataType {
+        LiteralType::bool(true).into()
+    }
+}
+
+#[allow(dead_code)]
+struct DeserializeVariant;
+
+impl Type for DeserializeVariant {
+    fn inline(_: &mut TypeCollection, _: Generics) -> DataType {
+        LiteralType::String("Deserialize".into()).into()
+    }
+}
@@ -2,12 +2,12 @@ use std::marker::PhantomData;
 
 use rspc_procedure::State;
 
-use crate::ProcedureMeta;
+use crate::SetupMeta;
 
 // TODO: `TError`?
 // TODO: Explain executor order and why to use over `Middleware`?
 pub struct Extension<TCtx, TInput, TResult> {
-    pub(crate) setup: Option<Box<dyn FnOnce(&mut State, ProcedureMeta) + 'static>>,
+    pub(crate) setup: Option<Box<dyn FnOnce(&mut State, SetupMeta) + 'static>>,
     pub(crate) phantom: PhantomData<fn() -> (TCtx, TInput, TResult)>,
     // pub(crate) inner: Box<
     //     dyn FnOnce(
@@ -28,7 +28,7 @@ impl<TCtx, TInput, TResult> Extension<TCtx, TInput, TResult> {
     }
 
     // TODO: Allow multiple or error if defined multiple times?
-    pub fn setup(mut self, func: impl FnOnce(&mut State, ProcedureMeta) + 'static) -> Self {
+    pub fn setup(mut self, func: impl FnOnce(&mut State, SetupMeta) + 'static) -> Self {
         self.setup = Some(Box::new(func));
         self
     }
@@ -1,3 +1,6 @@
+#[cfg(feature = "json-schema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-schema")))]
+mod json_schema;
 #[cfg(feature = "rust")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rust")))]
 mod rust;
@@ -5,9 +8,12 @@ mod rust;
 #[cfg_attr(docsrs, doc(cfg(feature = "typescript")))]
 mod typescript;
 
-// #[cfg(feature = "rust")]
-// #[cfg_attr(docsrs, doc(cfg(feature = "rust")))]
-// pub use rust::Rust; // TODO
+#[cfg(feature = "json-schema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "json-schema")))]
+pub use json_schema::{JsonSchema, JsonSchemaError, SchemaChange, SchemaChangeKind, SchemaDiff};
+#[cfg(feature = "rust")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rust")))]
+pub use rust::Rust;
 #[cfg(feature = "typescript")]
 #[cfg_attr(docsrs, doc(cfg(feature = "typescript")))]
 pub use typescript::Typescript;
@@ -0,0 +1,523 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    error, fmt,
+    path::Path,
+};
+
+use serde_json::{json, Map, Value};
+use specta::{
+    datatype::{
+        DataType, DeprecatedType, EnumRepr, EnumType, EnumVariants, Field, GenericType,
+        LiteralType, NamedDataType, PrimitiveType, StructFields, StructType,
+    },
+    ImplLocation, SpectaID, TypeCollection,
+};
+
+use crate::{ProcedureKind, Types};
+
+mod diff;
+
+pub use diff::{SchemaChange, SchemaChangeKind, SchemaDiff};
+
+/// Export the router as a [JSON Schema](https://json-schema.org) document.
+///
+/// The document contains a `procedures` object keyed by the dotted path of each procedure with it's `kind` and the schema of it's `input`, `output` and `error`.
+/// Named types are exported into `$defs` and referenced using `$ref`.
+///
+/// This is designed as a language-neutral description of the API that can be used to generate clients in other languages or diffed to detect changes.
+pub struct JsonSchema {
+    header: Cow<'static, str>,
+}
+
+// TODO: Traits - `Debug`, `Clone`, etc
+
+impl Default for JsonSchema {
+    fn default() -> Self {
+        Self {
+            header: "This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.".into(),
+        }
+    }
+}
+
+impl JsonSchema {
+    /// Set the `$comment` at the root of the document.
+    pub fn header(self, header: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            header: header.into(),
+        }
+    }
+
+    pub fn export_to(&self, path: impl AsRef<Path>, types: &Types) -> Result<(), JsonSchemaError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, self.export(types)?)?;
+        Ok(())
+    }
+
+    pub fn export(&self, types: &Types) -> Result<String, JsonSchemaError> {
+        // We only ever serialize a `Value` so this can't fail.
+        Ok(serde_json::to_string_pretty(&self.export_value(types)?)
+            .expect("unreachable: serializing a `serde_json::Value` is infallible"))
+    }
+
+    /// Compare a snapshot previously exported by [`JsonSchema::export`] with the current [`Types`].
+    ///
+    /// Refer to [`SchemaDiff`] for using this to catch breaking changes in CI.
+    pub fn diff(&self, snapshot: &str, types: &Types) -> Result<SchemaDiff, JsonSchemaError> {
+        Ok(SchemaDiff::new(
+            &serde_json::from_str(snapshot)?,
+            &self.export_value(types)?,
+        ))
+    }
+
+    /// Export the document as a [`serde_json::Value`] so it can be modified before being written.
+    pub fn export_value(&self, types: &Types) -> Result<Value, JsonSchemaError> {
+        let cx = Context {
+            types: types.types(),
+            inlining: &[],
+        };
+
+        let mut procedures = Map::new();
+        for (path, ty) in types.procedures() {
+            let mut procedure = json!({
+                "kind": match ty.kind() {
+                    ProcedureKind::Query => "query",
+                    ProcedureKind::Mutation => "mutation",
+                    ProcedureKind::Subscription => "subscription",
+                },
+                "input": datatype(ty.input(), cx, &[]),
+                "output": datatype(ty.output(), cx, &[]),
+                "error": datatype(ty.error(), cx, &[]),
+            });
+            if !ty.description().is_empty() {
+                procedure["description"] = ty.description().into();
+            }
+            if ty.deprecated().is_some() {
+                procedure["deprecated"] = true.into();
+            }
+            procedures.insert(path.join("."), procedure);
+        }
+
+        let mut defs = BTreeMap::new();
+        let mut locations = HashMap::new();
+        for (_, ndt) in types.types() {
+            // Generic types are inlined at their usage as JSON Schema has no concept of generics.
+            if ndt.inner.generics().is_some_and(|g| !g.is_empty()) {
+                continue;
+            }
+
+            // Types are referenced by their name so it must be unique.
+            let location = ndt.ext().map(|ext| *ext.impl_location());
+            if let Some(existing) = locations.insert(ndt.name().clone(), location) {
+                return Err(JsonSchemaError::DuplicateTypeName {
+                    name: ndt.name().clone(),
+                    first: existing,
+                    second: location,
+                });
+            }
+
+            defs.insert(ndt.name().to_string(), named_datatype(ndt, cx));
+        }
+
+        let mut out = Map::new();
+        out.insert(
+            "$schema".into(),
+            "https://json-schema.org/draft/2020-12/schema".into(),
+        );
+        if !self.header.is_empty() {
+            out.insert("$comment".into(), self.header.to_string().into());
+        }
+        out.insert("procedures".into(), procedures.into());
+        out.insert(
+            "$defs".into(),
+            defs.into_iter().collect::<Map<_, _>>().into(),
+        );
+        Ok(out.into())
+    }
+}
+
+/// An error exporting a [`JsonSchema`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JsonSchemaError {
+    /// Two different types have the same name so they can't both be exported into `$defs`.
+    ///
+    /// Rename one of them with `#[specta(rename = "...")]`. The locations are `None` for types which weren't declared with `#[derive(Type)]`.
+    DuplicateTypeName {
+        name: Cow<'static, str>,
+        first: Option<ImplLocation>,
+        second: Option<ImplLocation>,
+    },
+    /// The snapshot given to [`JsonSchema::diff`] is not valid JSON.
+    Json(serde_json::Error),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for JsonSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateTypeName {
+                name,
+                first,
+                second,
+            } => write!(
+                f,
+                "Multiple types named '{name}' were exported from {:?} and {:?}",
+                first.map(|l| l.as_str()),
+                second.map(|l| l.as_str())
+            ),
+            Self::Json(err) => write!(f, "Invalid JSON Schema snapshot: {err}"),
+            Self::Io(err) => write!(f, "IO error: {err}"),
+        }
+    }
+}
+
+impl error::Error for JsonSchemaError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::DuplicateTypeName { .. } => None,
+            Self::Json(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<serde_json::Error> for JsonSchemaError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+impl From<std::io::Error> for JsonSchemaError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// The state used while converting a [`DataType`] into a schema.
+#[derive(Clone, Copy)]
+struct Context<'a> {
+    types: &'a TypeCollection,
+    // The generic types which are currently being inlined. Used to stop at recursive generic types.
+    inlining: &'a [SpectaID],
+}
+
+fn named_datatype(ndt: &NamedDataType, cx: Context<'_>) -> Value {
+    let mut schema = datatype(&ndt.inner, cx, &[]);
+    if let Value::Object(obj) = &mut schema {
+        obj.insert("title".into(), ndt.name().to_string().into());
+        docs(obj, ndt.docs(), ndt.deprecated());
+    }
+    schema
+}
+
+fn docs(obj: &mut Map<String, Value>, docs: &str, deprecated: Option<&DeprecatedType>) {
+    if !docs.is_empty() {
+        obj.insert("description".into(), docs.trim().into());
+    }
+    if deprecated.is_some() {
+        obj.insert("deprecated".into(), true.into());
+    }
+}
+
+fn datatype(dt: &DataType, cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    match dt {
+        DataType::Any | DataType::Unknown => json!({}),
+        DataType::Primitive(p) => primitive(p),
+        DataType::Literal(l) => literal(l),
+        DataType::List(l) => {
+            let mut obj = Map::new();
+            obj.insert("type".into(), "array".into());
+            obj.insert("items".into(), datatype(l.ty(), cx, generics));
+            if let Some(length) = l.length() {
+                obj.insert("minItems".into(), length.into());
+                obj.insert("maxItems".into(), length.into());
+            }
+            if l.unique() {
+                obj.insert("uniqueItems".into(), true.into());
+            }
+            obj.into()
+        }
+        // JSON object keys are always strings so the key type is represented by `propertyNames`.
+        DataType::Map(m) => json!({
+            "type": "object",
+            "propertyNames": datatype(m.key_ty(), cx, generics),
+            "additionalProperties": datatype(m.value_ty(), cx, generics),
+        }),
+        DataType::Nullable(t) => json!({
+            "anyOf": [datatype(t, cx, generics), { "type": "null" }],
+        }),
+        DataType::Struct(s) => r#struct(s, cx, generics),
+        DataType::Enum(e) => r#enum(e, cx, generics),
+        DataType::Tuple(t) => tuple(t.elements(), cx, generics),
+        DataType::Reference(r) => {
+            if r.generics().is_empty() {
+                return json!({ "$ref": format!("#/$defs/{}", r.name()) });
+            }
+
+            // JSON Schema has no concept of generics so a recursive generic type (Eg. `struct Tree<T> { children: Vec<Tree<T>> }`) can only be expanded once.
+            if cx.inlining.contains(&r.sid()) {
+                return json!({});
+            }
+
+            // The generic arguments are resolved against the parent's generics (Eg. `struct A<T>(B<T>)`).
+            let generics = r
+                .generics()
+                .iter()
+                .map(|(g, dt)| (g.clone(), datatype(dt, cx, generics)))
+                .collect::<Vec<_>>();
+
+            match cx.types.get(r.sid()) {
+                Some(ndt) => {
+                    let inlining = cx
+                        .inlining
+                        .iter()
+                        .copied()
+                        .chain([r.sid()])
+                        .collect::<Vec<_>>();
+                    datatype(
+                        &ndt.inner,
+                        Context {
+                            inlining: &inlining,
+                            ..cx
+                        },
+                        &generics,
+                    )
+                }
+                // TODO: Should this be an error?
+                None => json!({ "$ref": format!("#/$defs/{}", r.name()) }),
+            }
+        }
+        DataType::Generic(g) => generics
+            .iter()
+            .find(|(name, _)| name == g)
+            .map(|(_, schema)| schema.clone())
+            .unwrap_or_else(|| json!({})),
+    }
+}
+
+fn primitive(p: &PrimitiveType) -> Value {
+    match p {
+        PrimitiveType::i8
+        | PrimitiveType::i16
+        | PrimitiveType::i32
+        | PrimitiveType::i64
+        | PrimitiveType::i128
+        | PrimitiveType::isize => json!({ "type": "integer", "format": p.to_rust_str() }),
+        PrimitiveType::u8
+        | PrimitiveType::u16
+        | PrimitiveType::u32
+        | PrimitiveType::u64
+        | PrimitiveType::u128
+        | PrimitiveType::usize => {
+            json!({ "type": "integer", "format": p.to_rust_str(), "minimum": 0 })
+        }
+        PrimitiveType::f32 | PrimitiveType::f64 => {
+            json!({ "type": "number", "format": p.to_rust_str() })
+        }
+        PrimitiveType::bool => json!({ "type": "boolean" }),
+        PrimitiveType::char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+        PrimitiveType::String => json!({ "type": "string" }),
+    }
+}
+
+fn literal(l: &LiteralType) -> Value {
+    match l {
+        LiteralType::i8(v) => json!({ "const": v }),
+        LiteralType::i16(v) => json!({ "const": v }),
+        LiteralType::i32(v) => json!({ "const": v }),
+        LiteralType::u8(v) => json!({ "const": v }),
+        LiteralType::u16(v) => json!({ "const": v }),
+        LiteralType::u32(v) => json!({ "const": v }),
+        LiteralType::f32(v) => json!({ "const": v }),
+        LiteralType::f64(v) => json!({ "const": v }),
+        LiteralType::bool(v) => json!({ "const": v }),
+        LiteralType::String(v) => json!({ "const": v }),
+        LiteralType::char(v) => json!({ "const": v }),
+        LiteralType::None => json!({ "type": "null" }),
+        _ => json!({}),
+    }
+}
+
+fn tuple(elements: &[DataType], cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    match elements {
+        [] => json!({ "type": "null" }),
+        elements => json!({
+            "type": "array",
+            "prefixItems": elements
+                .iter()
+                .map(|dt| datatype(dt, cx, generics))
+                .collect::<Vec<_>>(),
+            "minItems": elements.len(),
+            "maxItems": elements.len(),
+        }),
+    }
+}
+
+fn field(field: &Field, cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    let mut schema = field
+        .ty()
+        .map(|ty| datatype(ty, cx, generics))
+        .unwrap_or_else(|| json!({}));
+    if let Value::Object(obj) = &mut schema {
+        docs(obj, field.docs(), field.deprecated());
+    }
+    schema
+}
+
+fn unnamed_fields(fields: &[Field], cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    let fields = fields
+        .iter()
+        .filter(|f| f.ty().is_some())
+        .collect::<Vec<_>>();
+    match &fields[..] {
+        // A newtype is represented by it's inner type
+        [f] => field(f, cx, generics),
+        fields => json!({
+            "type": "array",
+            "prefixItems": fields
+                .iter()
+                .map(|f| field(f, cx, generics))
+                .collect::<Vec<_>>(),
+            "minItems": fields.len(),
+            "maxItems": fields.len(),
+        }),
+    }
+}
+
+/// Construct an object schema from it's named fields. Flattened fields are merged in using `allOf`.
+fn named_fields<'a>(
+    fields: impl Iterator<Item = (&'a Cow<'static, str>, &'a Field)>,
+    tag: Option<(&str, &str)>,
+    cx: Context<'_>,
+    generics: &[(GenericType, Value)],
+) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut flattened = Vec::new();
+
+    if let Some((tag, value)) = tag {
+        properties.insert(tag.into(), json!({ "const": value }));
+        required.push(Value::from(tag));
+    }
+
+    for (name, f) in fields {
+        if f.ty().is_none() {
+            continue;
+        }
+
+        if f.flatten() {
+            flattened.push(field(f, cx, generics));
+            continue;
+        }
+
+        properties.insert(name.to_string(), field(f, cx, generics));
+        if !f.optional() {
+            required.push(name.to_string().into());
+        }
+    }
+
+    let obj = json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    });
+
+    if flattened.is_empty() {
+        obj
+    } else {
+        flattened.insert(0, obj);
+        json!({ "allOf": flattened })
+    }
+}
+
+fn r#struct(s: &StructType, cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    match s.fields() {
+        StructFields::Unit => json!({ "type": "null" }),
+        StructFields::Unnamed(f) => unnamed_fields(f.fields(), cx, generics),
+        StructFields::Named(f) => named_fields(
+            f.fields().iter().map(|(name, f)| (name, f)),
+            s.tag().map(|tag| (&**tag, &**s.name())),
+            cx,
+            generics,
+        ),
+    }
+}
+
+fn r#enum(e: &EnumType, cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    let variants = e
+        .variants()
+        .iter()
+        .filter(|(_, v)| !v.skip())
+        .map(|(name, v)| {
+            let mut schema = match (e.repr(), v.inner()) {
+                (EnumRepr::Untagged, EnumVariants::Unit) => json!({ "type": "null" }),
+                (EnumRepr::Untagged, EnumVariants::Named(f)) => {
+                    named_fields(f.fields().iter().map(|(n, f)| (n, f)), None, cx, generics)
+                }
+                (EnumRepr::Untagged, EnumVariants::Unnamed(f)) => {
+                    unnamed_fields(f.fields(), cx, generics)
+                }
+                (EnumRepr::External, EnumVariants::Unit) => json!({ "const": name }),
+                (EnumRepr::External, inner) => json!({
+                    "type": "object",
+                    "properties": { name.to_string(): variant(inner, cx, generics) },
+                    "required": [name],
+                    "additionalProperties": false,
+                }),
+                (EnumRepr::Internal { tag }, EnumVariants::Unit) => {
+                    named_fields(std::iter::empty(), Some((tag, name)), cx, generics)
+                }
+                (EnumRepr::Internal { tag }, EnumVariants::Named(f)) => named_fields(
+                    f.fields().iter().map(|(n, f)| (n, f)),
+                    Some((tag, name)),
+                    cx,
+                    generics,
+                ),
+                // Serde only allows newtype variants here so the inner type must be an object.
+                (EnumRepr::Internal { tag }, EnumVariants::Unnamed(f)) => json!({
+                    "allOf": [
+                        named_fields(std::iter::empty(), Some((tag, name)), cx, generics),
+                        unnamed_fields(f.fields(), cx, generics),
+                    ]
+                }),
+                (EnumRepr::Adjacent { tag, .. }, EnumVariants::Unit) => {
+                    named_fields(std::iter::empty(), Some((tag, name)), cx, generics)
+                }
+                (EnumRepr::Adjacent { tag, content }, inner) => json!({
+                    "type": "object",
+                    "properties": {
+                        tag.to_string(): { "const": name },
+                        content.to_string(): variant(inner, cx, generics),
+                    },
+                    "required": [tag, content],
+                }),
+            };
+
+            if let Value::Object(obj) = &mut schema {
+                docs(obj, v.docs(), v.deprecated());
+            }
+            schema
+        })
+        .collect::<Vec<_>>();
+
+    match e.repr() {
+        // Untagged enums could overlap so they must use `anyOf`.
+        EnumRepr::Untagged => json!({ "anyOf": variants }),
+        _ => json!({ "oneOf": variants }),
+    }
+}
+
+fn variant(v: &EnumVariants, cx: Context<'_>, generics: &[(GenericType, Value)]) -> Value {
+    match v {
+        EnumVariants::Unit => json!({ "type": "null" }),
+        EnumVariants::Named(f) => {
+            named_fields(f.fields().iter().map(|(n, f)| (n, f)), None, cx, generics)
+        }
+        EnumVariants::Unnamed(f) => unnamed_fields(f.fields(), cx, generics),
+    }
+}
@@ -0,0 +1,520 @@
+use std::{collections::BTreeSet, fmt};
+
+use serde_json::{Map, Value};
+
+/// The differences between two schemas exported by [`JsonSchema`](super::JsonSchema).
+///
+/// This is designed to be used from a test so CI can fail when an unapproved breaking change is made:
+///
+/// ```ignore
+/// #[test]
+/// fn no_breaking_changes() {
+///     let (_, types) = router().build().unwrap();
+///     let diff = rspc::JsonSchema::default()
+///         .diff(include_str!("../schema.json"), &types)
+///         .unwrap();
+///
+///     // Once a break has been approved re-export `schema.json` to update the snapshot.
+///     assert!(!diff.is_breaking(), "{diff}");
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaDiff {
+    changes: Vec<SchemaChange>,
+}
+
+/// A single change between two schemas.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaChange {
+    /// The dotted path of the procedure. Eg. `users.get`
+    pub procedure: String,
+    /// The location within the procedure. Eg. `input.name` or `output[].id`
+    pub location: String,
+    pub kind: SchemaChangeKind,
+    breaking: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemaChangeKind {
+    ProcedureAdded,
+    ProcedureRemoved,
+    KindChanged { from: String, to: String },
+    FieldAdded { required: bool },
+    FieldRemoved { required: bool },
+    FieldRequired,
+    FieldOptional,
+    BecameNullable,
+    BecameNonNullable,
+    VariantAdded(String),
+    VariantRemoved(String),
+    TypeChanged,
+}
+
+/// Which way data is flowing for the part of the schema being compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    /// Sent by the client to the server (the input).
+    Input,
+    /// Sent by the server to the client (the output and error).
+    Output,
+}
+
+impl SchemaDiff {
+    /// Compare a previous snapshot of the schema with the current one.
+    pub fn new(previous: &Value, current: &Value) -> Self {
+        let empty = Map::new();
+        let procedures = |v: &Value| v.get("procedures").and_then(Value::as_object).cloned();
+        let (prev_procedures, cur_procedures) = (
+            procedures(previous).unwrap_or_default(),
+            procedures(current).unwrap_or_default(),
+        );
+
+        let mut diff = Differ {
+            prev_defs: previous
+                .get("$defs")
+                .and_then(Value::as_object)
+                .unwrap_or(&empty),
+            cur_defs: current
+                .get("$defs")
+                .and_then(Value::as_object)
+                .unwrap_or(&empty),
+            procedure: String::new(),
+            visited: BTreeSet::new(),
+            changes: Vec::new(),
+        };
+
+        for (name, prev) in &prev_procedures {
+            diff.procedure = name.clone();
+            diff.visited.clear();
+
+            let Some(cur) = cur_procedures.get(name) else {
+                diff.push(String::new(), SchemaChangeKind::ProcedureRemoved);
+                continue;
+            };
+
+            let (prev_kind, cur_kind) = (&prev["kind"], &cur["kind"]);
+            if prev_kind != cur_kind {
+                diff.push(
+                    String::new(),
+                    SchemaChangeKind::KindChanged {
+                        from: prev_kind.as_str().unwrap_or_default().to_string(),
+                        to: cur_kind.as_str().unwrap_or_default().to_string(),
+                    },
+                );
+            }
+
+            diff.compare(
+                "input".into(),
+                &prev["input"],
+                &cur["input"],
+                Direction::Input,
+            );
+            diff.compare(
+                "output".into(),
+                &prev["output"],
+                &cur["output"],
+                Direction::Output,
+            );
+            diff.compare(
+                "error".into(),
+                &prev["error"],
+                &cur["error"],
+                Direction::Output,
+            );
+        }
+
+        for name in cur_procedures.keys() {
+            if !prev_procedures.contains_key(name) {
+                diff.procedure = name.clone();
+                diff.push(String::new(), SchemaChangeKind::ProcedureAdded);
+            }
+        }
+
+        Self {
+            changes: diff.changes,
+        }
+    }
+
+    /// Every change between the two schemas.
+    pub fn changes(&self) -> &[SchemaChange] {
+        &self.changes
+    }
+
+    /// The changes which could break an existing client.
+    pub fn breaking(&self) -> impl Iterator<Item = &SchemaChange> + '_ {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+
+    /// Returns `true` if any change could break an existing client.
+    pub fn is_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "No changes");
+        }
+
+        for change in &self.changes {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl SchemaChange {
+    /// Returns `true` if the change could break an existing client.
+    pub fn is_breaking(&self) -> bool {
+        self.breaking
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = if self.breaking { "BREAKING" } else { "safe" };
+        write!(f, "[{severity}] {}", self.procedure)?;
+        if !self.location.is_empty() {
+            write!(f, " {}", self.location)?;
+        }
+        write!(f, ": ")?;
+
+        match &self.kind {
+            SchemaChangeKind::ProcedureAdded => write!(f, "procedure added"),
+            SchemaChangeKind::ProcedureRemoved => write!(f, "procedure removed"),
+            SchemaChangeKind::KindChanged { from, to } => {
+                write!(f, "procedure changed from a {from} to a {to}")
+            }
+            SchemaChangeKind::FieldAdded { required: true } => write!(f, "required field added"),
+            SchemaChangeKind::FieldAdded { required: false } => write!(f, "optional field added"),
+            SchemaChangeKind::FieldRemoved { required: true } => {
+                write!(f, "required field removed")
+            }
+            SchemaChangeKind::FieldRemoved { required: false } => {
+                write!(f, "optional field removed")
+            }
+            SchemaChangeKind::FieldRequired => write!(f, "field changed from optional to required"),
+            SchemaChangeKind::FieldOptional => write!(f, "field changed from required to optional"),
+            SchemaChangeKind::BecameNullable => write!(f, "type became nullable"),
+            SchemaChangeKind::BecameNonNullable => write!(f, "type is no longer nullable"),
+            SchemaChangeKind::VariantAdded(v) => write!(f, "variant '{v}' added"),
+            SchemaChangeKind::VariantRemoved(v) => write!(f, "variant '{v}' removed"),
+            SchemaChangeKind::TypeChanged => write!(f, "type changed"),
+        }
+    }
+}
+
+struct Differ<'a> {
+    prev_defs: &'a Map<String, Value>,
+    cur_defs: &'a Map<String, Value>,
+    procedure: String,
+    // The `$ref`'s which have already been compared. This is required to support recursive types.
+    visited: BTreeSet<(String, String, &'static str)>,
+    changes: Vec<SchemaChange>,
+}
+
+impl Differ<'_> {
+    fn push(&mut self, location: String, kind: SchemaChangeKind) {
+        self.push_with(location, kind, None);
+    }
+
+    fn push_with(
+        &mut self,
+        location: String,
+        kind: SchemaChangeKind,
+        direction: Option<Direction>,
+    ) {
+        let breaking = match (&kind, direction) {
+            (SchemaChangeKind::ProcedureAdded, _) => false,
+            (SchemaChangeKind::ProcedureRemoved, _) => true,
+            (SchemaChangeKind::KindChanged { .. }, _) => true,
+            (SchemaChangeKind::TypeChanged, _) => true,
+            // The server will ignore fields it doesn't know about.
+            (SchemaChangeKind::FieldAdded { required }, Some(Direction::Input)) => *required,
+            (SchemaChangeKind::FieldRemoved { .. }, Some(Direction::Input)) => false,
+            // The client will ignore fields it doesn't know about.
+            (SchemaChangeKind::FieldAdded { .. }, _) => false,
+            (SchemaChangeKind::FieldRemoved { required }, _) => *required,
+            // Accepting more values is safe for an input but not for an output as existing clients won't expect them.
+            (
+                SchemaChangeKind::FieldOptional
+                | SchemaChangeKind::BecameNullable
+                | SchemaChangeKind::VariantAdded(_),
+                direction,
+            ) => direction != Some(Direction::Input),
+            (
+                SchemaChangeKind::FieldRequired
+                | SchemaChangeKind::BecameNonNullable
+                | SchemaChangeKind::VariantRemoved(_),
+                direction,
+            ) => direction == Some(Direction::Input),
+        };
+
+        self.changes.push(SchemaChange {
+            procedure: self.procedure.clone(),
+            location,
+            kind,
+            breaking,
+        });
+    }
+
+    fn compare(&mut self, location: String, prev: &Value, cur: &Value, direction: Direction) {
+        // Named types are compared by their definition so we don't report a change for a rename.
+        let (prev, prev_ref) = resolve(prev, self.prev_defs);
+        let (cur, cur_ref) = resolve(cur, self.cur_defs);
+        if let (Some(prev_ref), Some(cur_ref)) = (prev_ref, cur_ref) {
+            let dir = match direction {
+                Direction::Input => "input",
+                Direction::Output => "output",
+            };
+            if !self
+                .visited
+                .insert((prev_ref.to_string(), cur_ref.to_string(), dir))
+            {
+                return;
+            }
+        }
+
+        if prev == cur {
+            return;
+        }
+
+        let (prev, prev_nullable) = split_nullable(prev);
+        let (cur, cur_nullable) = split_nullable(cur);
+        match (prev_nullable, cur_nullable) {
+            (false, true) => {
+                self.push_with(
+                    location.clone(),
+                    SchemaChangeKind::BecameNullable,
+                    Some(direction),
+                );
+            }
+            (true, false) => {
+                self.push_with(
+                    location.clone(),
+                    SchemaChangeKind::BecameNonNullable,
+                    Some(direction),
+                );
+            }
+            _ => {}
+        }
+
+        // `allOf` is used for flattened fields so we merge them into a single object.
+        let prev = merge_all_of(prev, self.prev_defs);
+        let cur = merge_all_of(cur, self.cur_defs);
+
+        match (variants(&prev), variants(&cur)) {
+            (Some(prev), Some(cur)) => {
+                for (key, prev) in &prev {
+                    match cur.iter().find(|(k, _)| k == key) {
+                        Some((_, cur)) => {
+                            self.compare(format!("{location}|{key}"), prev, cur, direction)
+                        }
+                        None => self.push_with(
+                            location.clone(),
+                            SchemaChangeKind::VariantRemoved(key.clone()),
+                            Some(direction),
+                        ),
+                    }
+                }
+
+                for (key, _) in &cur {
+                    if !prev.iter().any(|(k, _)| k == key) {
+                        self.push_with(
+                            location.clone(),
+                            SchemaChangeKind::VariantAdded(key.clone()),
+                            Some(direction),
+                        );
+                    }
+                }
+                return;
+            }
+            (None, None) => {}
+            _ => return self.push(location, SchemaChangeKind::TypeChanged),
+        }
+
+        if prev.get("type") != cur.get("type")
+            || prev.get("format") != cur.get("format")
+            || prev.get("const") != cur.get("const")
+        {
+            return self.push(location, SchemaChangeKind::TypeChanged);
+        }
+
+        if let (Some(prev_props), Some(cur_props)) = (
+            prev.get("properties").and_then(Value::as_object),
+            cur.get("properties").and_then(Value::as_object),
+        ) {
+            let (prev_required, cur_required) = (required(&prev), required(&cur));
+
+            for (key, prev_ty) in prev_props {
+                let field = format!("{location}.{key}");
+                let was_required = prev_required.contains(key.as_str());
+                match cur_props.get(key) {
+                    Some(cur_ty) => {
+                        match (was_required, cur_required.contains(key.as_str())) {
+                            (false, true) => self.push_with(
+                                field.clone(),
+                                SchemaChangeKind::FieldRequired,
+                                Some(direction),
+                            ),
+                            (true, false) => self.push_with(
+                                field.clone(),
+                                SchemaChangeKind::FieldOptional,
+                                Some(direction),
+                            ),
+                            _ => {}
+                        }
+
+                        self.compare(field, prev_ty, cur_ty, direction);
+                    }
+                    None => self.push_with(
+                        field,
+                        SchemaChangeKind::FieldRemoved {
+                            required: was_required,
+                        },
+                        Some(direction),
+                    ),
+                }
+            }
+
+            for key in cur_props.keys() {
+                if !prev_props.contains_key(key) {
+                    self.push_with(
+                        format!("{location}.{key}"),
+                        SchemaChangeKind::FieldAdded {
+                            required: cur_required.contains(key.as_str()),
+                        },
+                        Some(direction),
+                    );
+                }
+            }
+        }
+
+        if let (Some(prev), Some(cur)) = (prev.get("items"), cur.get("items")) {
+            self.compare(format!("{location}[]"), prev, cur, direction);
+        }
+
+        match (
+            prev.get("prefixItems").and_then(Value::as_array),
+            cur.get("prefixItems").and_then(Value::as_array),
+        ) {
+            (Some(prev), Some(cur)) if prev.len() == cur.len() => {
+                for (i, (prev, cur)) in prev.iter().zip(cur).enumerate() {
+                    self.compare(format!("{location}[{i}]"), prev, cur, direction);
+                }
+            }
+            (None, None) => {}
+            _ => return self.push(location, SchemaChangeKind::TypeChanged),
+        }
+
+        if let (Some(prev), Some(cur)) = (
+            prev.get("additionalProperties"),
+            cur.get("additionalProperties"),
+        ) {
+            self.compare(format!("{location}{{}}"), prev, cur, direction);
+        }
+    }
+}
+
+/// Resolve a `$ref` into it's definition.
+fn resolve<'a>(v: &'a Value, defs: &'a Map<String, Value>) -> (&'a Value, Option<&'a str>) {
+    match v
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix("#/$defs/"))
+    {
+        Some(name) => (defs.get(name).unwrap_or(v), Some(name)),
+        None => (v, None),
+    }
+}
+
+/// Split `{ "anyOf": [T, { "type": "null" }] }` into `T` and whether it is nullable.
+fn split_nullable(v: &Value) -> (&Value, bool) {
+    if let Some([inner, null]) = v.get("anyOf").and_then(Value::as_array).map(|v| &v[..]) {
+        if null.get("type").and_then(Value::as_str) == Some("null") {
+            return (inner, true);
+        }
+    }
+
+    (v, false)
+}
+
+fn merge_all_of(v: &Value, defs: &Map<String, Value>) -> Value {
+    let Some(all_of) = v.get("allOf").and_then(Value::as_array) else {
+        return v.clone();
+    };
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for item in all_of {
+        let item = merge_all_of(resolve(item, defs).0, defs);
+        if let Some(props) = item.get("properties").and_then(Value::as_object) {
+            properties.extend(props.clone());
+        }
+        if let Some(r) = item.get("required").and_then(Value::as_array) {
+            required.extend(r.iter().cloned());
+        }
+    }
+
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+fn required(v: &Value) -> BTreeSet<&str> {
+    v.get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Get the variants of an enum keyed by a stable identifier so they can be matched up even if they are reordered.
+fn variants(v: &Value) -> Option<Vec<(String, &Value)>> {
+    let variants = v
+        .get("oneOf")
+        .or_else(|| v.get("anyOf"))
+        .and_then(Value::as_array)?;
+
+    Some(
+        variants
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (variant_key(v).unwrap_or_else(|| i.to_string()), v))
+            .collect(),
+    )
+}
+
+fn variant_key(v: &Value) -> Option<String> {
+    // Unit variant of an externally tagged enum or a literal
+    if let Some(c) = v.get("const") {
+        return Some(c.as_str().map(ToString::to_string).unwrap_or(c.to_string()));
+    }
+
+    if v.get("type").and_then(Value::as_str) == Some("null") {
+        return Some("null".into());
+    }
+
+    // Internally or adjacently tagged
+    let v = v
+        .get("allOf")
+        .and_then(Value::as_array)
+        .and_then(|v| v.first())
+        .unwrap_or(v);
+    let properties = v.get("properties").and_then(Value::as_object)?;
+    if let Some(tag) = properties
+        .values()
+        .find_map(|p| p.get("const").and_then(Value::as_str))
+    {
+        return Some(tag.into());
+    }
+
+    // Externally tagged
+    match (properties.len(), v.get("additionalProperties")) {
+        (1, Some(Value::Bool(false))) => properties.keys().next().cloned(),
+        _ => None,
+    }
+}
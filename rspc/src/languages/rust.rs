@@ -1,85 +1,618 @@
-//! TODO: Bring this back when published.
-// use std::{borrow::Cow, collections::HashMap, path::Path};
-
-// use specta::datatype::DataType;
-// use specta_typescript::ExportError;
-
-// use crate::{procedure::ProcedureType, types::TypesOrType, ProcedureKind, Types};
-
-// pub struct Rust(()); // TODO: specta_rust::Rust
-
-// // TODO: Traits - `Debug`, `Clone`, etc
-
-// impl Default for Rust {
-//     fn default() -> Self {
-//         Self(()) // TODO: specta_typescript::Typescript::default().framework_header("// This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.")
-//     }
-// }
-
-// impl Rust {
-//     // TODO: Clone all methods from `specta_rust::Rust`
-
-//     pub fn export_to(&self, path: impl AsRef<Path>, types: &Types) -> Result<(), ExportError> {
-//         std::fs::write(path, self.export(types)?)?;
-//         // TODO: Format file
-//         Ok(())
-//     }
-
-//     pub fn export(&self, types: &Types) -> Result<String, ExportError> {
-//         println!("WARNING: `rspc::Rust` is an unstable feature! Use at your own discretion!");
-
-//         let mut s = "//! This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.\n\npub struct Procedures;\n\n".to_string();
-
-//         // TODO: Move to `specta_rust::Rust` which should handle this like we do with Typescript.
-//         for (_, ty) in types.types.into_iter() {
-//             s.push_str(&specta_rust::export_named_datatype(ty).unwrap())
-//         }
-
-//         // TODO: disabling warning on the output???
-
-//         for (key, item) in types.procedures.clone().into_iter() {
-//             export(&mut s, key.to_string(), key, item);
-//         }
-
-//         Ok(s)
-//     }
-// }
-
-// fn export(s: &mut String, full_key: String, ident: Cow<'static, str>, item: TypesOrType) {
-//     match item {
-//         TypesOrType::Type(ty) => {
-//             let kind = match ty.kind {
-//                 ProcedureKind::Query => "Query",
-//                 ProcedureKind::Mutation => "Mutation",
-//                 ProcedureKind::Subscription => "Subscription",
-//             };
-
-//             let input = specta_rust::datatype(&ty.input).unwrap();
-//             let output = specta_rust::datatype(&ty.output).unwrap();
-//             let error = "()"; // TODO: specta_rust::datatype(&ty.error).unwrap();
-
-//             s.push_str(&format!(
-//                 r#"pub struct {ident};
-
-// impl rspc_client::Procedure for {ident} {{
-//     type Input = {input};
-//     type Output = {output};
-//     type Error = {error};
-//     type Procedures = Procedures;
-//     const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::{kind};
-//     const KEY: &'static str = "{ident}";
-// }}
-
-// "#
-//             ));
-//         }
-//         TypesOrType::Types(inner) => {
-//             for (key, item) in inner {
-//                 s.push_str(&format!("\npub mod {key} {{\n"));
-//                 s.push_str(&"\tpub use super::Procedures;\n");
-//                 export(s, format!("{full_key}.{key}"), key, item); // TODO: Inset all items by the correct nuber of tabs
-//                 s.push_str("}\n");
-//             }
-//         }
-//     }
-// }
+use std::{borrow::Cow, collections::BTreeMap, io, path::Path, process::Command};
+
+use specta::{
+    datatype::{
+        DataType, DeprecatedType, EnumRepr, EnumType, EnumVariants, Field, LiteralType,
+        NamedDataType, PrimitiveType, StructFields, StructType,
+    },
+    SpectaID, TypeCollection,
+};
+
+use crate::{procedure::ProcedureType, types::TypesOrType, ProcedureKind, Types};
+
+/// Export the router as Rust bindings for [`rspc_client`](https://docs.rs/rspc-client).
+///
+/// This generates a Rust definition for every type used by the router along with a struct implementing `rspc_client::Procedure` for each procedure.
+/// Nested routers are exported as nested modules (Eg. `users.get` becomes `users::get`).
+///
+/// The generated code only depends on `rspc_client`.
+///
+/// Be aware this is an unstable feature. Use at your own discretion!
+pub struct Rust {
+    header: Cow<'static, str>,
+    formatter: Option<fn(&Path) -> io::Result<()>>,
+}
+
+impl Default for Rust {
+    fn default() -> Self {
+        Self {
+            header: "//! This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.".into(),
+            formatter: None,
+        }
+    }
+}
+
+impl Rust {
+    pub fn header(self, header: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            header: header.into(),
+            ..self
+        }
+    }
+
+    /// Run a formatter on the file after it's written by [`Rust::export_to`]. Eg. [`Rust::rustfmt`].
+    pub fn formatter(self, formatter: fn(&Path) -> io::Result<()>) -> Self {
+        Self {
+            formatter: Some(formatter),
+            ..self
+        }
+    }
+
+    /// Format a file using `rustfmt`. This requires `rustfmt` to be installed.
+    pub fn rustfmt(path: &Path) -> io::Result<()> {
+        let output = Command::new("rustfmt")
+            .arg("--edition=2021")
+            .arg(path)
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "rustfmt failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn export_to(&self, path: impl AsRef<Path>, types: &Types) -> Result<(), io::Error> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        std::fs::write(path, self.export(types))?;
+        if let Some(formatter) = self.formatter {
+            formatter(path)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn export(&self, types: &Types) -> String {
+        let mut s = self.header.to_string();
+        if !s.is_empty() {
+            s.push_str("\n\n");
+        }
+        s.push_str(
+            "#![allow(non_camel_case_types, non_snake_case, dead_code, unused_imports, deprecated)]\n\n",
+        );
+        s.push_str("pub struct Procedures;\n");
+
+        let mut exporter = Exporter {
+            types: types.types(),
+            definitions: BTreeMap::new(),
+            pending: Vec::new(),
+        };
+
+        let mut procedures = String::new();
+        for (key, item) in &types.procedures {
+            exporter.procedures(&mut procedures, &[], key, item);
+        }
+
+        // TODO: Handle two types with the same name in different modules
+        for (_, ndt) in types.types() {
+            exporter.named(ndt.name(), ndt);
+        }
+        // Named types which were inlined into a procedure are not in the `TypeCollection` so we define them when we find them.
+        while let Some(dt) = exporter.pending.pop() {
+            match &dt {
+                DataType::Struct(st) => exporter.r#struct(st.name(), st, "", false),
+                DataType::Enum(e) => exporter.r#enum(e.name(), e, "", false),
+                _ => {}
+            }
+        }
+
+        for definition in exporter.definitions.values() {
+            s.push('\n');
+            s.push_str(definition);
+        }
+        s.push_str(&procedures);
+        s
+    }
+}
+
+struct Exporter<'a> {
+    types: &'a TypeCollection,
+    definitions: BTreeMap<String, String>,
+    pending: Vec<DataType>,
+}
+
+impl Exporter<'_> {
+    fn procedures(&mut self, s: &mut String, path: &[&str], key: &str, item: &TypesOrType) {
+        let depth = path.len();
+        let mut full_path = path.to_vec();
+        full_path.push(key);
+
+        match item {
+            TypesOrType::Type(ty) => {
+                let mut out = String::new();
+                self.procedure(&mut out, &full_path, ty);
+                s.push_str(&indent(&out, depth));
+            }
+            TypesOrType::Types(inner) => {
+                s.push_str(&indent(
+                    &format!("\npub mod {} {{\n    use super::*;\n", ident(key)),
+                    depth,
+                ));
+                for (key, item) in inner {
+                    self.procedures(s, &full_path, key, item);
+                }
+                s.push_str(&indent("}\n", depth));
+            }
+        }
+    }
+
+    fn procedure(&mut self, s: &mut String, path: &[&str], ty: &ProcedureType) {
+        let name = ident(path.last().copied().unwrap_or_default());
+        let key = path.join(".");
+        let kind = match ty.kind {
+            ProcedureKind::Query => "Query",
+            ProcedureKind::Mutation => "Mutation",
+            ProcedureKind::Subscription => "Subscription",
+        };
+        let input = self.datatype(&ty.input);
+        let output = self.datatype(&ty.output);
+        let error = self.datatype(&ty.error);
+        let mut attrs = docs(&ty.description);
+        if let Some(deprecated) = &ty.deprecated {
+            attrs.push_str(&match deprecated {
+                DeprecatedType::DeprecatedWithSince { note, .. } if !note.is_empty() => {
+                    format!("#[deprecated = {:?}]\n", note.trim())
+                }
+                _ => "#[deprecated]\n".into(),
+            });
+        }
+
+        s.push_str(&format!(
+            r#"
+{attrs}pub struct {name};
+
+impl rspc_client::Procedure for {name} {{
+    type Input = {input};
+    type Output = {output};
+    type Error = {error};
+    type Procedures = Procedures;
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::{kind};
+    const KEY: &'static str = "{key}";
+}}
+"#
+        ));
+    }
+
+    fn named(&mut self, name: &str, ndt: &NamedDataType) {
+        let mut attrs = docs(ndt.docs());
+        if ndt.deprecated().is_some() {
+            attrs.push_str("#[deprecated]\n");
+        }
+
+        match &ndt.inner {
+            DataType::Struct(st) => self.r#struct(name, st, &attrs, true),
+            DataType::Enum(e) => self.r#enum(name, e, &attrs, true),
+            dt => {
+                let generics = generics(dt.generics().map(|g| &g[..]).unwrap_or_default());
+                let ty = self.datatype(dt);
+                self.definitions.insert(
+                    name.to_string(),
+                    format!("{attrs}pub type {}{generics} = {ty};\n", ident(name)),
+                );
+            }
+        }
+    }
+
+    fn r#struct(&mut self, name: &str, st: &StructType, attrs: &str, overwrite: bool) {
+        if !overwrite && self.definitions.contains_key(name) {
+            return;
+        }
+        let mut s = attrs.to_string();
+        s.push_str(&derive(self.struct_derives(st, &mut Vec::new())));
+        if let Some(tag) = st.tag() {
+            s.push_str(&format!("#[serde(tag = {tag:?})]\n"));
+        }
+
+        let name_ident = ident(name);
+        let generics = generics(st.generics());
+        match st.fields() {
+            StructFields::Unit => s.push_str(&format!("pub struct {name_ident}{generics};\n")),
+            StructFields::Unnamed(f) => {
+                let fields = self.unnamed_fields(f.fields(), "pub ");
+                s.push_str(&format!("pub struct {name_ident}{generics}({fields});\n"));
+            }
+            StructFields::Named(f) => {
+                s.push_str(&format!("pub struct {name_ident}{generics} {{\n"));
+                for (name, field) in f.fields() {
+                    s.push_str(&indent(&self.named_field(name, field, "pub "), 1));
+                }
+                s.push_str("}\n");
+            }
+        }
+
+        self.definitions.insert(name.to_string(), s);
+    }
+
+    fn r#enum(&mut self, name: &str, e: &EnumType, attrs: &str, overwrite: bool) {
+        if !overwrite && self.definitions.contains_key(name) {
+            return;
+        }
+        let mut s = attrs.to_string();
+        s.push_str(&derive(self.enum_derives(e, &mut Vec::new())));
+        match e.repr() {
+            EnumRepr::External => {}
+            EnumRepr::Untagged => s.push_str("#[serde(untagged)]\n"),
+            EnumRepr::Internal { tag } => s.push_str(&format!("#[serde(tag = {tag:?})]\n")),
+            EnumRepr::Adjacent { tag, content } => {
+                s.push_str(&format!("#[serde(tag = {tag:?}, content = {content:?})]\n"))
+            }
+        }
+        s.push_str(&format!(
+            "pub enum {}{} {{\n",
+            ident(name),
+            generics(e.generics())
+        ));
+
+        for (name, variant) in e.variants() {
+            // Skipped variants can't be serialized or deserialized so there is no point exporting them.
+            if variant.skip() {
+                continue;
+            }
+
+            let mut v = docs(variant.docs());
+            v.push_str(&rename(name));
+            v.push_str(&ident(name));
+            match variant.inner() {
+                EnumVariants::Unit => v.push_str(",\n"),
+                EnumVariants::Unnamed(f) => {
+                    v.push_str(&format!("({}),\n", self.unnamed_fields(f.fields(), "")));
+                }
+                EnumVariants::Named(f) => {
+                    v.push_str(" {\n");
+                    for (name, field) in f.fields() {
+                        v.push_str(&indent(&self.named_field(name, field, ""), 1));
+                    }
+                    v.push_str("},\n");
+                }
+            }
+            s.push_str(&indent(&v, 1));
+        }
+        s.push_str("}\n");
+
+        self.definitions.insert(name.to_string(), s);
+    }
+
+    fn unnamed_fields(&mut self, fields: &[Field], vis: &str) -> String {
+        fields
+            .iter()
+            .filter_map(|f| f.ty())
+            .map(|ty| format!("{vis}{}", self.datatype(ty)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn named_field(&mut self, name: &str, field: &Field, vis: &str) -> String {
+        let Some(ty) = field.ty() else {
+            return String::new();
+        };
+
+        let mut s = docs(field.docs());
+        if field.flatten() {
+            s.push_str("#[serde(flatten)]\n");
+        } else {
+            s.push_str(&rename(name));
+        }
+
+        let mut ty = self.datatype(ty);
+        if field.optional() {
+            s.push_str("#[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+            if !matches!(field.ty(), Some(DataType::Nullable(_))) {
+                ty = format!("Option<{ty}>");
+            }
+        }
+
+        s.push_str(&format!("{vis}{}: {ty},\n", ident(name)));
+        s
+    }
+
+    fn struct_derives(&self, st: &StructType, visiting: &mut Vec<SpectaID>) -> Derives {
+        match st.fields() {
+            StructFields::Unit => Derives::ALL,
+            StructFields::Unnamed(f) => self.fields_derives(f.fields().iter(), visiting),
+            StructFields::Named(f) => {
+                self.fields_derives(f.fields().iter().map(|(_, f)| f), visiting)
+            }
+        }
+    }
+
+    fn enum_derives(&self, e: &EnumType, visiting: &mut Vec<SpectaID>) -> Derives {
+        e.variants()
+            .iter()
+            .filter(|(_, variant)| !variant.skip())
+            .fold(Derives::ALL, |derives, (_, variant)| {
+                derives.and(match variant.inner() {
+                    EnumVariants::Unit => Derives::ALL,
+                    EnumVariants::Unnamed(f) => self.fields_derives(f.fields().iter(), visiting),
+                    EnumVariants::Named(f) => {
+                        self.fields_derives(f.fields().iter().map(|(_, f)| f), visiting)
+                    }
+                })
+            })
+    }
+
+    fn fields_derives<'b>(
+        &self,
+        fields: impl Iterator<Item = &'b Field>,
+        visiting: &mut Vec<SpectaID>,
+    ) -> Derives {
+        fields
+            .filter_map(|f| f.ty())
+            .fold(Derives::ALL, |derives, ty| {
+                derives.and(self.derives(ty, visiting))
+            })
+    }
+
+    /// Determine which traits can be derived for a type containing `dt`. This must match the Rust type emitted by [`Exporter::datatype`].
+    fn derives(&self, dt: &DataType, visiting: &mut Vec<SpectaID>) -> Derives {
+        match dt {
+            // `serde_json::Value` only implements `Hash` in recent versions.
+            DataType::Any | DataType::Unknown => Derives::EQ,
+            DataType::Primitive(PrimitiveType::f32 | PrimitiveType::f64)
+            | DataType::Literal(LiteralType::f32(_) | LiteralType::f64(_)) => Derives::NONE,
+            DataType::Primitive(_) | DataType::Literal(_) | DataType::Generic(_) => Derives::ALL,
+            DataType::List(l) => self.derives(l.ty(), visiting),
+            // `HashMap` doesn't implement `Hash`.
+            DataType::Map(m) => self
+                .derives(m.key_ty(), visiting)
+                .and(self.derives(m.value_ty(), visiting))
+                .and(Derives::EQ),
+            DataType::Nullable(t) => self.derives(t, visiting),
+            DataType::Struct(st) if !st.name().is_empty() && st.generics().is_empty() => {
+                self.struct_derives(st, visiting)
+            }
+            DataType::Enum(e) if !e.name().is_empty() && e.generics().is_empty() => {
+                self.enum_derives(e, visiting)
+            }
+            DataType::Struct(_) | DataType::Enum(_) => Derives::EQ,
+            DataType::Tuple(t) => t.elements().iter().fold(Derives::ALL, |derives, ty| {
+                derives.and(self.derives(ty, visiting))
+            }),
+            DataType::Reference(r) => {
+                let derives = r.generics().iter().fold(Derives::ALL, |derives, (_, ty)| {
+                    derives.and(self.derives(ty, visiting))
+                });
+
+                // A recursive type doesn't restrict what can be derived any further.
+                let sid = r.sid();
+                if visiting.contains(&sid) {
+                    return derives;
+                }
+                let Some(ndt) = self.types.get(sid) else {
+                    return derives.and(Derives::NONE);
+                };
+
+                visiting.push(sid);
+                let inner = match &ndt.inner {
+                    DataType::Struct(st) => self.struct_derives(st, visiting),
+                    DataType::Enum(e) => self.enum_derives(e, visiting),
+                    dt => self.derives(dt, visiting),
+                };
+                visiting.pop();
+                derives.and(inner)
+            }
+        }
+    }
+
+    fn datatype(&mut self, dt: &DataType) -> String {
+        match dt {
+            DataType::Any | DataType::Unknown => "rspc_client::internal::serde_json::Value".into(),
+            DataType::Primitive(p) => p.to_rust_str().into(),
+            // Rust doesn't have literal types so we use the type of the literal.
+            DataType::Literal(l) => match l {
+                LiteralType::i8(_) => "i8",
+                LiteralType::i16(_) => "i16",
+                LiteralType::i32(_) => "i32",
+                LiteralType::u8(_) => "u8",
+                LiteralType::u16(_) => "u16",
+                LiteralType::u32(_) => "u32",
+                LiteralType::f32(_) => "f32",
+                LiteralType::f64(_) => "f64",
+                LiteralType::bool(_) => "bool",
+                LiteralType::String(_) => "String",
+                LiteralType::char(_) => "char",
+                _ => "()",
+            }
+            .into(),
+            DataType::List(l) => match l.length() {
+                Some(length) => format!("[{}; {length}]", self.datatype(l.ty())),
+                None => format!("Vec<{}>", self.datatype(l.ty())),
+            },
+            DataType::Map(m) => format!(
+                "std::collections::HashMap<{}, {}>",
+                self.datatype(m.key_ty()),
+                self.datatype(m.value_ty())
+            ),
+            DataType::Nullable(t) => format!("Option<{}>", self.datatype(t)),
+            DataType::Struct(st) if !st.name().is_empty() && st.generics().is_empty() => {
+                self.pending.push(dt.clone());
+                ident(st.name())
+            }
+            DataType::Enum(e) if !e.name().is_empty() && e.generics().is_empty() => {
+                self.pending.push(dt.clone());
+                ident(e.name())
+            }
+            // TODO: Anonymous or generic structs and enums which have been inlined
+            DataType::Struct(_) | DataType::Enum(_) => {
+                "rspc_client::internal::serde_json::Value".into()
+            }
+            DataType::Tuple(t) => match &t.elements()[..] {
+                [] => "()".into(),
+                // A single element tuple needs a trailing comma to not just be parenthesized
+                [ty] => format!("({},)", self.datatype(ty)),
+                elements => format!(
+                    "({})",
+                    elements
+                        .iter()
+                        .map(|ty| self.datatype(ty))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            DataType::Reference(r) => {
+                if r.generics().is_empty() {
+                    ident(r.name())
+                } else {
+                    format!(
+                        "{}<{}>",
+                        ident(r.name()),
+                        r.generics()
+                            .iter()
+                            .map(|(_, ty)| self.datatype(ty))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            }
+            DataType::Generic(g) => g.to_string(),
+        }
+    }
+}
+
+/// The traits which can be derived for a type on top of `Debug`, `Clone` and `PartialEq`.
+#[derive(Clone, Copy)]
+struct Derives {
+    eq: bool,
+    hash: bool,
+}
+
+impl Derives {
+    const ALL: Self = Self {
+        eq: true,
+        hash: true,
+    };
+    const EQ: Self = Self {
+        eq: true,
+        hash: false,
+    };
+    const NONE: Self = Self {
+        eq: false,
+        hash: false,
+    };
+
+    fn and(self, other: Self) -> Self {
+        Self {
+            eq: self.eq && other.eq,
+            hash: self.hash && other.hash,
+        }
+    }
+}
+
+fn derive(derives: Derives) -> String {
+    let mut traits = vec!["Debug", "Clone", "PartialEq"];
+    if derives.eq {
+        traits.push("Eq");
+        // `Hash` must agree with `Eq` so we only derive it alongside it.
+        if derives.hash {
+            traits.push("Hash");
+        }
+    }
+    traits.push("rspc_client::internal::serde::Serialize");
+    traits.push("rspc_client::internal::serde::Deserialize");
+
+    format!(
+        "#[derive({})]\n#[serde(crate = \"rspc_client::internal::serde\")]\n",
+        traits.join(", ")
+    )
+}
+
+// https://doc.rust-lang.org/reference/keywords.html
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "else", "enum", "extern", "false", "fn", "for", "if",
+    "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "type", "unsafe", "use", "where", "while", "async", "await", "dyn",
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof", "unsized",
+    "virtual", "yield", "try", "gen",
+];
+
+/// Convert a string into a valid Rust identifier.
+fn ident(name: &str) -> String {
+    let mut s = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if s.is_empty() || s.starts_with(|c: char| c.is_numeric()) {
+        s.insert(0, '_');
+    }
+
+    match &*s {
+        // These can't be raw identifiers
+        "self" | "Self" | "super" | "crate" => format!("{s}_"),
+        s if KEYWORDS.contains(&s) => format!("r#{s}"),
+        _ => s,
+    }
+}
+
+/// Emit a `#[serde(rename)]` if the identifier doesn't match the serialized name.
+fn rename(name: &str) -> String {
+    if ident(name).trim_start_matches("r#") == name {
+        String::new()
+    } else {
+        format!("#[serde(rename = {name:?})]\n")
+    }
+}
+
+fn generics(generics: &[impl ToString]) -> String {
+    if generics.is_empty() {
+        return String::new();
+    }
+
+    format!(
+        "<{}>",
+        generics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn docs(docs: &str) -> String {
+    if docs.trim().is_empty() {
+        return String::new();
+    }
+
+    docs.lines()
+        .map(|l| {
+            format!(
+                "///{}{}\n",
+                if l.is_empty() { "" } else { " " },
+                l.trim_end()
+            )
+        })
+        .collect()
+}
+
+fn indent(s: &str, depth: usize) -> String {
+    let padding = "    ".repeat(depth);
+    s.lines()
+        .map(|l| {
+            if l.is_empty() {
+                "\n".to_string()
+            } else {
+                format!("{padding}{l}\n")
+            }
+        })
+        .collect()
+}
@@ -1,14 +1,23 @@
-use std::{borrow::Cow, collections::BTreeMap, iter::once, path::Path};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    iter::once,
+    panic::Location,
+    path::{Path, PathBuf},
+};
 
 use serde_json::json;
-use specta::{datatype::DataType, NamedType, Type};
+use specta::{
+    datatype::{DataType, DeprecatedType},
+    NamedType, Type,
+};
 use specta_typescript::{
     datatype, export_named_datatype, BigIntExportBehavior, CommentFormatterFn, ExportError,
     FormatterFn,
 };
 
 use crate::{
-    procedure::ProcedureType, types::TypesOrType, util::literal_object, ProcedureKind, Types,
+    error::DeserializeErrorType, types::TypesOrType, util::literal_object, ProcedureKind, Types,
 };
 
 pub struct Typescript {
@@ -56,6 +65,9 @@ impl Typescript {
         }
     }
 
+    /// Generate a [source map](https://sourcemaps.info/spec.html) so "go to definition" on a procedure (or nested router) key jumps to where it was defined in Rust.
+    ///
+    /// When exporting to `bindings.ts` the `Procedures` type will be written to `bindings_t.d.ts` along with `bindings_t.d.ts.map` and re-exported from `bindings.ts`.
     pub fn enable_source_maps(mut self) -> Self {
         self.generate_source_maps = true;
         self
@@ -86,56 +98,66 @@ impl Typescript {
             std::fs::create_dir_all(parent)?;
         }
 
+        let invalid_path = || {
+            ExportError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("invalid bindings path '{}'", path.display()),
+            ))
+        };
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(invalid_path)?;
+        let dir = path.parent().ok_or_else(invalid_path)?;
+
         let mut bindings = self.export(types)?;
         if self.generate_source_maps {
-            println!(
-                "WARNING: Typescript source maps in an unstable feature. Use at your own discretion!"
-            );
-
-            bindings += "export { Procedures } from './bindings_t';";
+            bindings += &format!("export type {{ Procedures }} from './{stem}_t';\n");
         } else {
-            generate_bindings(&mut bindings, self, types, |_, _, _| {});
+            generate_bindings(&mut bindings, self, types, |_, _, _| {})?;
         }
         std::fs::write(&path, bindings)?;
         self.inner.format(&path)?;
 
         if self.generate_source_maps {
-            let stem = path.file_stem().unwrap().to_str().unwrap().to_string(); // TODO: Error handling
             let d_ts_file_name = format!("{stem}_t.d.ts");
             let d_ts_map_file_name = format!("{stem}_t.d.ts.map");
-            let d_ts_path = path.parent().unwrap().join(&d_ts_file_name); // TODO: Error handling
-            let d_ts_map_path = path.parent().unwrap().join(&d_ts_map_file_name); // TODO: Error handling
-
-            // let mut bindings = construct_file(self);
-            // bindings += "export * from './bindings_t';";
 
             let mut source_map = SourceMap::default();
             let mut d_ts_file = construct_file(self);
-            generate_bindings(&mut d_ts_file, self, types, |name, pos, procedure_type| {
+
+            let named_types = types
+                .types
+                .into_iter()
+                .map(|(_, ndt)| ndt.name().to_string())
+                .collect::<BTreeSet<_>>();
+            if !named_types.is_empty() {
+                d_ts_file += &format!(
+                    "import type {{ {} }} from './{stem}';\n\n",
+                    named_types.into_iter().collect::<Vec<_>>().join(", ")
+                );
+            }
+
+            generate_bindings(&mut d_ts_file, self, types, |name, pos, location| {
                 source_map.insert(
                     name.to_string(),
                     pos,
                     (
-                        // TODO: Don't cast
-                        procedure_type.location.line() as usize,
-                        procedure_type.location.column() as usize,
+                        location.line() as usize,
+                        // Rust columns are 1-indexed but source maps are 0-indexed
+                        (location.column() as usize).saturating_sub(1),
                     ),
-                    procedure_type.location.file().to_string(),
+                    source_path(location.file(), dir),
                 );
-            });
-            d_ts_file += &format!("\n//# sourceMappingURL={d_ts_map_file_name}");
+            })?;
+            d_ts_file += &format!("\n//# sourceMappingURL={d_ts_map_file_name}\n");
 
-            // std::fs::write(&path, bindings)?;
-            std::fs::write(&d_ts_path, d_ts_file)?;
+            // We don't run the formatter on this file as it would invalidate the source map.
+            std::fs::write(dir.join(&d_ts_file_name), d_ts_file)?;
             std::fs::write(
-                &d_ts_map_path,
-                source_map.generate(
-                    d_ts_file_name.into(),
-                    "".into(), // TODO
-                ),
+                dir.join(&d_ts_map_file_name),
+                source_map.generate(d_ts_file_name.into()),
             )?;
-
-            self.inner.format(&d_ts_path)?;
         }
 
         Ok(())
@@ -161,6 +183,9 @@ impl Typescript {
             typess.insert(ProceduresLegacy::sid(), ndt);
         }
 
+        // Any procedure can return this so it's always exported.
+        typess.register::<DeserializeErrorType>();
+
         self.inner.export(&typess)
     }
 
@@ -171,22 +196,19 @@ fn generate_bindings(
     out: &mut String,
     this: &Typescript,
     types: &Types,
-    mut on_procedure: impl FnMut(&Cow<'static, str>, (usize, usize), &ProcedureType),
-) {
+    mut on_location: impl FnMut(&Cow<'static, str>, (usize, usize), Location<'static>),
+) -> Result<(), ExportError> {
     fn inner(
         out: &mut String,
         this: &Typescript,
-        on_procedure: &mut impl FnMut(&Cow<'static, str>, (usize, usize), &ProcedureType),
+        on_location: &mut impl FnMut(&Cow<'static, str>, (usize, usize), Location<'static>),
         types: &Types,
-        source_pos: (usize, usize),
-        key: &Cow<'static, str>,
+        path: &mut Vec<Cow<'static, str>>,
         item: &TypesOrType,
-    ) {
+    ) -> Result<(), ExportError> {
+        let indent = "\t".repeat(path.len());
         match item {
             TypesOrType::Type(procedure_type) => {
-                on_procedure(&key, source_pos, procedure_type);
-
-                // *out += "\t"; // TODO: Correct padding
                 *out += "{ kind: ";
                 *out += match procedure_type.kind {
                     ProcedureKind::Query => r#""query""#,
@@ -199,61 +221,114 @@ fn generate_bindings(
                     &this.inner,
                     &specta::datatype::FunctionResultVariant::Value(procedure_type.input.clone()),
                     &types.types,
-                )
-                .unwrap(); // TODO: Error handling
+                )?;
 
                 *out += ", output: ";
                 *out += &datatype(
                     &this.inner,
                     &specta::datatype::FunctionResultVariant::Value(procedure_type.output.clone()),
                     &types.types,
-                )
-                .unwrap(); // TODO: Error handling
+                )?;
 
                 *out += ", error: ";
                 *out += &datatype(
                     &this.inner,
                     &specta::datatype::FunctionResultVariant::Value(procedure_type.error.clone()),
                     &types.types,
-                )
-                .unwrap(); // TODO: Error handling
+                )?;
 
                 *out += " }";
             }
             TypesOrType::Types(btree_map) => {
-                // TODO: Jump to definition on routers
-                // *out += "name: ";
-
                 *out += "{\n";
 
                 for (key, item) in btree_map.iter() {
+                    if let TypesOrType::Type(ty) = item {
+                        if this.inner.comment_exporter.is_some() {
+                            js_doc(
+                                out,
+                                &format!("{indent}\t"),
+                                ty.description(),
+                                ty.deprecated(),
+                            );
+                        }
+                    }
+
+                    *out += &indent;
                     *out += "\t";
 
                     let source_pos = get_current_pos(out);
+                    path.push(key.clone());
+                    match item {
+                        TypesOrType::Type(ty) => on_location(key, source_pos, ty.location),
+                        TypesOrType::Types(_) => {
+                            if let Some(location) = types.nested.get(&*path) {
+                                on_location(key, source_pos, *location);
+                            }
+                        }
+                    }
 
                     *out += key;
                     *out += ": ";
-                    inner(out, this, on_procedure, types, source_pos, key, &item);
+                    inner(out, this, on_location, types, path, item)?;
                     *out += ",\n";
+                    path.pop();
                 }
 
+                *out += &indent;
                 *out += "}";
             }
         }
+
+        Ok(())
     }
 
     *out += "export type Procedures = ";
     inner(
         out,
         this,
-        &mut on_procedure,
+        &mut on_location,
         types,
-        // We know this is only used in `TypesOrType::Type` and we don't parse that so it's value means nothing.
-        (0, 0),
-        // We know this is only used in `TypesOrType::Type` and we don't parse that so it's value means nothing.
-        &"".into(),
+        &mut Vec::new(),
         &TypesOrType::Types(types.procedures.clone()),
-    );
+    )
+}
+
+/// Render a JSDoc comment in the same format as `specta_typescript::comments::js_doc`.
+fn js_doc(out: &mut String, indent: &str, docs: &str, deprecated: Option<&DeprecatedType>) {
+    if docs.is_empty() && deprecated.is_none() {
+        return;
+    }
+
+    *out += indent;
+    *out += "/**\n";
+    if !docs.is_empty() {
+        for line in docs.lines() {
+            *out += indent;
+            *out += " * ";
+            *out += &line.trim().replace("*/", "*\\/");
+            *out += "\n";
+        }
+    }
+
+    if let Some(deprecated) = deprecated {
+        *out += indent;
+        *out += " * @deprecated";
+        if let DeprecatedType::DeprecatedWithSince { since, note } = deprecated {
+            if !note.is_empty() {
+                *out += " ";
+                *out += &note.trim().replace("*/", "*\\/");
+            }
+            if let Some(since) = since {
+                *out += " since ";
+                *out += since.trim();
+            }
+        }
+        *out += "\n";
+    }
+
+    *out += indent;
+    *out += " */\n";
 }
 
 fn construct_file(this: &Typescript) -> String {
@@ -281,15 +356,8 @@ impl SourceMap {
         source_pos: (usize, usize),
         source_file: String,
     ) {
-        if !self.sources.contains(&source_file) {
-            self.sources.push(source_file.clone());
-        }
-        let source_id = self.sources.iter().position(|s| *s == source_file).unwrap();
-
-        if !self.names.contains(&name) {
-            self.names.push(name.clone());
-        }
-        let name_id = self.names.iter().position(|s| *s == name).unwrap();
+        let source_id = index_of(&mut self.sources, source_file);
+        let name_id = index_of(&mut self.names, name);
 
         self.mappings
             .entry(generated_line)
@@ -297,15 +365,18 @@ impl SourceMap {
             .push((generated_col, source_id, name_id, source_pos));
     }
 
-    pub fn generate(&self, file: Cow<'static, str>, source_base_path: Cow<'static, str>) -> String {
+    pub fn generate(&self, file: Cow<'static, str>) -> String {
         let mut mappings = String::new();
-        let mut last_source_line = None::<usize>;
-        let mut last_source_col = None::<usize>;
-        let mut last_source_file = None::<usize>;
-        let mut last_name_id = None::<usize>;
+        // Every field (other than the generated column) is relative to the previous segment in the file.
+        // These can go backwards so they must be signed.
+        let mut last_source_line = 0i64;
+        let mut last_source_col = 0i64;
+        let mut last_source_file = 0i64;
+        let mut last_name_id = 0i64;
 
         for i in 1..((self.mappings.keys().max().copied().unwrap_or(0)) + 1) {
-            let mut last_col = None::<usize>;
+            // The generated column is relative to the previous segment on the same line.
+            let mut last_col = 0i64;
 
             if let Some(line_mappings) = self.mappings.get(&i) {
                 for (
@@ -322,38 +393,29 @@ impl SourceMap {
                         mappings.push(',');
                     }
 
-                    let col = last_col.map(|l| actual_col - l).unwrap_or(*actual_col);
-                    last_col = Some(*actual_col);
-
-                    let actual_source_line = *actual_source_line - 1;
-                    let source_line = last_source_line
-                        .map(|l| actual_source_line - l)
-                        .unwrap_or(actual_source_line);
-                    last_source_line = Some(actual_source_line);
-
-                    let source_col = last_source_col
-                        .map(|l| actual_source_col - l)
-                        .unwrap_or(*actual_source_col);
-                    last_source_col = Some(*actual_source_col);
-
-                    let source_file = last_source_file
-                        .map(|l| actual_source_file - l)
-                        .unwrap_or(*actual_source_file);
-                    last_source_file = Some(*actual_source_file);
+                    // Source maps use 0-indexed lines
+                    let actual_source_line = actual_source_line.saturating_sub(1);
+                    let [col, source_file, source_line, source_col, name_id] = [
+                        *actual_col,
+                        *actual_source_file,
+                        actual_source_line,
+                        *actual_source_col,
+                        *actual_name_id,
+                    ]
+                    .map(|v| i64::try_from(v).unwrap_or(i64::MAX));
 
-                    let name_id = last_name_id
-                        .map(|l| actual_name_id - l)
-                        .unwrap_or(*actual_name_id);
-                    last_name_id = Some(*actual_name_id);
-
-                    // TODO: Don't integer cast
                     let input = [
-                        col as i64,
-                        source_file as i64,
-                        source_line as i64,
-                        source_col as i64,
-                        name_id as i64,
+                        col - last_col,
+                        source_file - last_source_file,
+                        source_line - last_source_line,
+                        source_col - last_source_col,
+                        name_id - last_name_id,
                     ];
+                    last_col = col;
+                    last_source_file = source_file;
+                    last_source_line = source_line;
+                    last_source_col = source_col;
+                    last_name_id = name_id;
 
                     mappings.push_str(&generate_vlq_segment(&input));
                 }
@@ -365,10 +427,7 @@ impl SourceMap {
         serde_json::to_string(&json!({
             "version": 3,
             "file": file,
-            "sources":self.sources
-                .iter()
-                .map(|n| format!("{source_base_path}{n}"))
-                .collect::<Vec<_>>(),
+            "sources": self.sources,
             "names": self.names,
             "mappings": mappings
         }))
@@ -376,8 +435,79 @@ impl SourceMap {
     }
 }
 
-fn get_current_pos(s: &String) -> (usize, usize) {
-    (s.split("\n").count(), s.split("\n").last().unwrap().len())
+fn index_of(items: &mut Vec<String>, item: String) -> usize {
+    match items.iter().position(|s| *s == item) {
+        Some(i) => i,
+        None => {
+            items.push(item);
+            items.len() - 1
+        }
+    }
+}
+
+/// Get the current line (1-indexed) and column (0-indexed in UTF-16 code units as required by source maps).
+fn get_current_pos(s: &str) -> (usize, usize) {
+    (
+        s.matches('\n').count() + 1,
+        s.rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .encode_utf16()
+            .count(),
+    )
+}
+
+/// Resolve the path of a Rust source file relative to the directory of the source map.
+///
+/// [`Location::file`] is relative to the directory `rustc` was invoked in (the workspace root) so we search upwards from the current package to find it.
+fn source_path(file: &str, dir: &Path) -> String {
+    let file_path = Path::new(file);
+    let absolute = if file_path.is_absolute() {
+        Some(file_path.to_path_buf())
+    } else {
+        std::env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(std::env::current_dir().ok())
+            .flat_map(|base| {
+                base.ancestors()
+                    .map(|a| a.join(file_path))
+                    .collect::<Vec<_>>()
+            })
+            .find(|p| p.exists())
+    };
+
+    let relative = absolute.and_then(|absolute| {
+        let absolute = absolute.canonicalize().ok()?;
+        let dir = dir
+            .canonicalize()
+            .or_else(|_| std::env::current_dir().map(|cwd| cwd.join(dir)))
+            .ok()?;
+        relative_path(&dir, &absolute)
+    });
+
+    relative
+        .as_deref()
+        .unwrap_or(file_path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn relative_path(from: &Path, to: &Path) -> Option<PathBuf> {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    // Eg. different drives on Windows
+    if from.first() != to.first() {
+        return None;
+    }
+
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    path.extend(&to[common..]);
+    Some(path)
 }
 
 // Following copied from: https://docs.rs/sourcemap/latest/src/sourcemap/vlq.rs.html#307-313
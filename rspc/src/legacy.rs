@@ -1,10 +1,10 @@
 //! TODO: Explain how to do it.
 
-use std::{borrow::Cow, collections::BTreeMap, panic::Location};
+use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
 
-use futures_util::{stream, FutureExt, StreamExt, TryStreamExt};
+use futures_util::{stream, stream::BoxStream, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
 use rspc_legacy::internal::{Layer, RequestContext, ValueOrStream};
-use rspc_procedure::{ProcedureStream, ResolverError};
+use rspc_procedure::{ErrorCode, ProcedureError, ProcedureStream, ResolverError};
 use serde_json::Value;
 use specta::{
     datatype::{DataType, EnumRepr, EnumVariant, LiteralType},
@@ -12,10 +12,11 @@ use specta::{
 };
 
 use crate::{
+    middleware::{exec_router_middleware, RouterMiddlewareHandler, RouterOutput},
     procedure::{ErasedProcedure, ProcedureType},
     types::TypesOrType,
     util::literal_object,
-    ProcedureKind,
+    ProcedureKind, ProcedureMeta,
 };
 
 impl<TCtx> From<rspc_legacy::Router<TCtx>> for crate::Router<TCtx> {
@@ -34,25 +35,28 @@ impl<TCtx> From<rspc_legacy::Router<TCtx>> for crate::Router<TCtx> {
                     .map(|v| (ProcedureKind::Subscription, v)),
             )
             .map(|(kind, (key, p))| {
+                let exec: Arc<dyn Layer<TCtx>> = p.exec.into();
                 (
                     key.split(".")
                         .map(|s| s.to_string().into())
                         .collect::<Vec<Cow<'static, str>>>(),
                     ErasedProcedure {
                         kind,
-                        location: Location::caller().clone(), // TODO: This needs to actually be correct
+                        location: p.location,
                         setup: Default::default(),
-                        inner: Box::new(move |_, types| {
+                        middleware: Default::default(),
+                        inner: Box::new(move |meta, middleware, _| {
                             (
-                                layer_to_procedure(key.to_string(), kind, p.exec),
+                                layer_to_procedure(meta, kind, exec.clone(), middleware.into()),
                                 ProcedureType {
                                     kind,
                                     input: p.ty.arg_ty.clone(),
                                     output: p.ty.result_ty.clone(),
                                     error: specta::datatype::DataType::Unknown,
-                                    // TODO: This location is obviously wrong but the legacy router has no location information.
-                                    // This will work properly with the new procedure syntax.
-                                    location: Location::caller().clone(), // TODO: This needs to actually be correct
+                                    location: p.location,
+                                    description: Cow::Borrowed(""),
+                                    deprecated: None,
+                                    metadata: Vec::new(),
                                 },
                             )
                         }),
@@ -72,71 +76,113 @@ impl<TCtx> From<rspc_legacy::Router<TCtx>> for crate::Router<TCtx> {
 }
 
 pub(crate) fn layer_to_procedure<TCtx: 'static>(
-    path: String,
+    meta: ProcedureMeta,
     kind: ProcedureKind,
-    value: Box<dyn Layer<TCtx>>,
+    value: Arc<dyn Layer<TCtx>>,
+    middleware: Arc<[RouterMiddlewareHandler<TCtx>]>,
 ) -> rspc_procedure::Procedure<TCtx> {
-    rspc_procedure::Procedure::new(move |ctx, input| {
-        let result = input.deserialize::<Value>().and_then(|input| {
-            value
-                .call(
-                    ctx,
-                    input,
-                    RequestContext {
-                        kind: match kind {
-                            ProcedureKind::Query => rspc_legacy::internal::ProcedureKind::Query,
-                            ProcedureKind::Mutation => {
-                                rspc_legacy::internal::ProcedureKind::Mutation
-                            }
-                            ProcedureKind::Subscription => {
-                                rspc_legacy::internal::ProcedureKind::Subscription
-                            }
-                        },
-                        path: path.clone(),
-                    },
-                )
-                .map_err(|err| {
-                    let err: rspc_legacy::Error = err.into();
-                    ResolverError::new(
-                        (), /* typesafe errors aren't supported in legacy router */
-                        Some(rspc_procedure::LegacyErrorInterop(err.message().into())),
-                    )
-                    .into()
-                })
-        });
+    let path = meta.name().to_string();
+    rspc_procedure::Procedure::new(kind.into(), move |ctx, input| {
+        let input = match input.deserialize::<Value>() {
+            Ok(input) => input,
+            Err(err) => {
+                return ProcedureStream::from_stream(stream::once(async move { Err::<(), _>(err) }))
+            }
+        };
+
+        if middleware.is_empty() {
+            return ProcedureStream::from_stream(exec_layer(&value, &path, kind, ctx, input));
+        }
+
+        // The legacy procedure is run as the innermost layer of the `Router::with` middleware.
+        let value = value.clone();
+        let path = path.clone();
+        ProcedureStream::from_stream(
+            exec_router_middleware(
+                middleware.clone(),
+                0,
+                meta.clone(),
+                ctx,
+                Box::new(move |ctx| {
+                    let stream = exec_layer(&value, &path, kind, ctx, input);
+                    Box::pin(async move { Ok(RouterOutput(Box::new(stream))) })
+                }),
+            )
+            .map(|result| result.and_then(RouterOutput::downcast::<LegacyStream>))
+            .try_flatten_stream(),
+        )
+    })
+}
 
-        match result {
-            Ok(result) => ProcedureStream::from_stream(
-                async move {
-                    match result.into_value_or_stream().await {
-                        Ok(ValueOrStream::Value(value)) => {
-                            stream::once(async { Ok(value) }).boxed()
-                        }
-                        Ok(ValueOrStream::Stream(s)) => s
-                            .map_err(|err| {
-                                let err = rspc_legacy::Error::from(err);
-                                ResolverError::new(
-                                    (), /* typesafe errors aren't supported in legacy router */
-                                    Some(rspc_procedure::LegacyErrorInterop(err.message().into())),
-                                )
-                                .into()
-                            })
-                            .boxed(),
-                        Err(err) => {
-                            let err: rspc_legacy::Error = err.into();
-                            let err = ResolverError::new(err.message().to_string(), err.cause());
-                            stream::once(async { Err(err.into()) }).boxed()
-                        }
+type LegacyStream = BoxStream<'static, Result<Value, ProcedureError>>;
+
+fn exec_layer<TCtx: 'static>(
+    value: &Arc<dyn Layer<TCtx>>,
+    path: &str,
+    kind: ProcedureKind,
+    ctx: TCtx,
+    input: Value,
+) -> LegacyStream {
+    let result = value
+        .call(
+            ctx,
+            input,
+            RequestContext {
+                kind: match kind {
+                    ProcedureKind::Query => rspc_legacy::internal::ProcedureKind::Query,
+                    ProcedureKind::Mutation => rspc_legacy::internal::ProcedureKind::Mutation,
+                    ProcedureKind::Subscription => {
+                        rspc_legacy::internal::ProcedureKind::Subscription
                     }
+                },
+                path: path.to_string(),
+            },
+        )
+        .map_err(|err| {
+            let err: rspc_legacy::Error = err.into();
+            ResolverError::new(
+                (), /* typesafe errors aren't supported in legacy router */
+                Some(rspc_procedure::LegacyErrorInterop(err.message().into())),
+            )
+            .with_code(legacy_error_code(&err))
+            .into()
+        });
+
+    match result {
+        Ok(result) => async move {
+            match result.into_value_or_stream().await {
+                Ok(ValueOrStream::Value(value)) => stream::once(async { Ok(value) }).boxed(),
+                Ok(ValueOrStream::Stream(s)) => s
+                    .map_err(|err| {
+                        let err = rspc_legacy::Error::from(err);
+                        ResolverError::new(
+                            (), /* typesafe errors aren't supported in legacy router */
+                            Some(rspc_procedure::LegacyErrorInterop(err.message().into())),
+                        )
+                        .with_code(legacy_error_code(&err))
+                        .into()
+                    })
+                    .boxed(),
+                Err(err) => {
+                    let err: rspc_legacy::Error = err.into();
+                    let code = legacy_error_code(&err);
+                    let err =
+                        ResolverError::new(err.message().to_string(), err.cause()).with_code(code);
+                    stream::once(async { Err(err.into()) }).boxed()
                 }
-                .into_stream()
-                .flatten(),
-            ),
-            Err(err) => {
-                ProcedureStream::from_stream(stream::once(async move { Err::<(), _>(err) }))
             }
         }
-    })
+        .into_stream()
+        .flatten()
+        .boxed(),
+        Err(err) => stream::once(async move { Err(err) }).boxed(),
+    }
+}
+
+// Legacy errors have their own `ErrorCode` which covers a subset of ours.
+fn legacy_error_code(err: &rspc_legacy::Error) -> ErrorCode {
+    ErrorCode::from_status_code(err.code().to_status_code())
+        .unwrap_or(ErrorCode::InternalServerError)
 }
 
 fn map_method(
@@ -16,6 +16,7 @@
 pub mod middleware;
 
 mod as_date;
+mod caller;
 mod error;
 mod extension;
 mod languages;
@@ -31,21 +32,24 @@ pub(crate) mod util;
 pub mod legacy;
 
 pub use as_date::AsDate;
+pub use caller::{CallError, Caller};
 pub use error::Error;
 pub use extension::Extension;
 #[allow(unused)]
 pub use languages::*;
 pub use procedure::{
-    ErasedProcedure, Procedure, ProcedureBuilder, ProcedureMeta, ResolverInput, ResolverOutput,
+    ErasedProcedure, Procedure, ProcedureBuilder, ProcedureMeta, ProcedureType, ResolverInput,
+    ResolverOutput, SetupMeta,
 };
 pub use procedure_kind::ProcedureKind;
-pub use router::Router;
+pub use router::{BuildError, DuplicateProcedureKeyError, Router, UnknownAliasTargetError};
 pub use stream::Stream;
 pub use types::Types;
 
 // We only re-export types that are useful for a general user.
 pub use rspc_procedure::{
-    flush, DynInput, ProcedureError, ProcedureStream, Procedures, ResolverError, State,
+    closing, flush, DeserializeError, DynInput, ErrorCode, LogMessage, PathSegment, ProcedureError,
+    ProcedureStream, Procedures, ResolverError, SerializerError, State,
 };
 
 // TODO: Potentially remove these once Axum stuff is sorted.
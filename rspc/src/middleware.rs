@@ -1,9 +1,14 @@
 mod into_middleware;
+mod lifecycle;
 mod middleware;
 mod next;
+mod router;
 
+pub use lifecycle::{EndReason, Lifecycle};
 pub use middleware::Middleware;
 pub use next::Next;
+pub use router::{RouterNext, RouterOutput};
 
 pub(crate) use into_middleware::IntoMiddleware;
 pub(crate) use middleware::MiddlewareHandler;
+pub(crate) use router::{exec_router_middleware, RouterMiddlewareHandler};
@@ -0,0 +1,221 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use futures_util::{FutureExt, Stream};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type ItemHook<T> = Box<dyn FnMut(T) -> T + Send>;
+
+/// Why a [`Lifecycle`] stream ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndReason {
+    /// The stream yielded all of it's values.
+    Complete,
+    /// The client unsubscribed or disconnected. Refer to [`ProcedureStream::close`](crate::ProcedureStream::close).
+    Unsubscribed,
+    /// The stream was dropped without being closed (Eg. the async runtime shutting down).
+    ///
+    /// The hook's future is only polled once so it should do it's work synchronously.
+    Dropped,
+}
+
+/// Wrap a subscription's [`Stream`] to run hooks when it starts, yields a value and ends.
+///
+/// This can be returned from a [`subscription`](crate::ProcedureBuilder::subscription) or a subscription [`Middleware`](super::Middleware).
+///
+/// The end hooks run whether the stream completes or the client unsubscribes or disconnects.
+/// When unsubscribing the integration will continue to poll the stream until the hooks have finished (refer to [`closing`](crate::closing)).
+///
+/// ```rust
+/// use rspc::middleware::{EndReason, Lifecycle};
+///
+/// let stream = Lifecycle::new(futures::stream::iter([1, 2, 3]))
+///     .on_start(|| async { println!("joined") })
+///     .on_item(|v| v * 2)
+///     .on_end(|reason| async move { println!("left: {reason:?}") });
+/// ```
+#[must_use = "streams do nothing unless polled"]
+pub struct Lifecycle<S: Stream> {
+    stream: Option<Pin<Box<S>>>,
+    state: LifecycleState,
+    on_start: Vec<Box<dyn FnOnce() -> BoxFuture + Send>>,
+    on_item: Vec<ItemHook<S::Item>>,
+    on_end: Vec<Box<dyn FnOnce(EndReason) -> BoxFuture + Send>>,
+}
+
+enum LifecycleState {
+    Init,
+    Starting(BoxFuture),
+    Streaming,
+    Ending(BoxFuture),
+    Done,
+}
+
+impl<S: Stream + Send + 'static> Lifecycle<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream: Some(Box::pin(stream)),
+            state: LifecycleState::Init,
+            on_start: Default::default(),
+            on_item: Default::default(),
+            on_end: Default::default(),
+        }
+    }
+
+    /// Run a hook before the stream is polled for the first time.
+    pub fn on_start<F: Future<Output = ()> + Send + 'static>(
+        mut self,
+        func: impl FnOnce() -> F + Send + 'static,
+    ) -> Self {
+        self.on_start.push(Box::new(move || Box::pin(func())));
+        self
+    }
+
+    /// Intercept each value yielded by the stream.
+    pub fn on_item(mut self, func: impl FnMut(S::Item) -> S::Item + Send + 'static) -> Self {
+        self.on_item.push(Box::new(func));
+        self
+    }
+
+    /// Run a hook once the stream has ended for any reason.
+    ///
+    /// The stream will be dropped before the hook runs.
+    pub fn on_end<F: Future<Output = ()> + Send + 'static>(
+        mut self,
+        func: impl FnOnce(EndReason) -> F + Send + 'static,
+    ) -> Self {
+        self.on_end
+            .push(Box::new(move |reason| Box::pin(func(reason))));
+        self
+    }
+
+    /// Run a hook once the stream has yielded all of it's values.
+    pub fn on_complete<F: Future<Output = ()> + Send + 'static>(
+        self,
+        func: impl FnOnce() -> F + Send + 'static,
+    ) -> Self {
+        self.on_end(move |reason| {
+            let fut = (reason == EndReason::Complete).then(func);
+            async move {
+                if let Some(fut) = fut {
+                    fut.await;
+                }
+            }
+        })
+    }
+
+    /// Run a hook once the client has unsubscribed or disconnected.
+    pub fn on_unsubscribe<F: Future<Output = ()> + Send + 'static>(
+        self,
+        func: impl FnOnce() -> F + Send + 'static,
+    ) -> Self {
+        self.on_end(move |reason| {
+            let fut = (reason != EndReason::Complete).then(func);
+            async move {
+                if let Some(fut) = fut {
+                    fut.await;
+                }
+            }
+        })
+    }
+}
+
+impl<S: Stream> Lifecycle<S> {
+    fn end(&mut self, reason: EndReason) -> BoxFuture {
+        self.stream = None;
+        let hooks = std::mem::take(&mut self.on_end);
+        Box::pin(async move {
+            for hook in hooks {
+                hook(reason).await;
+            }
+        })
+    }
+}
+
+impl<S: Stream> Stream for Lifecycle<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        // This must be checked on every poll to acknowledge the close, even while the hooks are running.
+        let closing = crate::closing();
+
+        loop {
+            match &mut this.state {
+                LifecycleState::Init | LifecycleState::Starting(_) | LifecycleState::Streaming
+                    if closing =>
+                {
+                    this.state = LifecycleState::Ending(this.end(EndReason::Unsubscribed));
+                }
+                LifecycleState::Init => {
+                    let hooks = std::mem::take(&mut this.on_start);
+                    this.state = LifecycleState::Starting(Box::pin(async move {
+                        for hook in hooks {
+                            hook().await;
+                        }
+                    }));
+                }
+                LifecycleState::Starting(fut) => {
+                    ready!(fut.as_mut().poll(cx));
+                    this.state = LifecycleState::Streaming;
+                }
+                LifecycleState::Streaming => {
+                    let Some(stream) = &mut this.stream else {
+                        unreachable!("the stream is only dropped once ending");
+                    };
+
+                    match ready!(stream.as_mut().poll_next(cx)) {
+                        Some(mut item) => {
+                            for hook in &mut this.on_item {
+                                item = hook(item);
+                            }
+                            return Poll::Ready(Some(item));
+                        }
+                        None => {
+                            this.state = LifecycleState::Ending(this.end(EndReason::Complete));
+                        }
+                    }
+                }
+                LifecycleState::Ending(fut) => {
+                    ready!(fut.as_mut().poll(cx));
+                    this.state = LifecycleState::Done;
+                }
+                LifecycleState::Done => return Poll::Ready(None),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match (&self.state, &self.stream) {
+            (LifecycleState::Done | LifecycleState::Ending(_), _) | (_, None) => (0, Some(0)),
+            (_, Some(stream)) => stream.size_hint(),
+        }
+    }
+}
+
+impl<S: Stream> Drop for Lifecycle<S> {
+    fn drop(&mut self) {
+        let fut = match std::mem::replace(&mut self.state, LifecycleState::Done) {
+            LifecycleState::Ending(fut) => fut,
+            LifecycleState::Done => return,
+            _ => self.end(EndReason::Dropped),
+        };
+
+        // We have no way to wait for the hooks so we give them a single chance to run.
+        let _ = fut.now_or_never();
+    }
+}
+
+impl<S: Stream> fmt::Debug for Lifecycle<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lifecycle")
+            .field("on_start", &self.on_start.len())
+            .field("on_item", &self.on_item.len())
+            .field("on_end", &self.on_end.len())
+            .finish()
+    }
+}
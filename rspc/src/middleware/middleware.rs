@@ -21,10 +21,10 @@
 
 use std::{pin::Pin, sync::Arc};
 
-use futures_util::{Future, FutureExt, Stream};
+use futures_util::{future::ready, stream::BoxStream, Future, FutureExt, Stream, StreamExt};
 use rspc_procedure::State;
 
-use crate::ProcedureMeta;
+use crate::{ProcedureMeta, SetupMeta};
 
 use super::Next;
 
@@ -88,7 +88,7 @@ pub struct Middleware<
     TNextInput = TThisInput,
     TNextResult = TThisResult,
 > {
-    pub(crate) setup: Option<Box<dyn FnOnce(&mut State, ProcedureMeta) + 'static>>,
+    pub(crate) setup: Option<Box<dyn FnOnce(&mut State, SetupMeta) + 'static>>,
     pub(crate) inner: Box<
         dyn FnOnce(
             MiddlewareHandler<TError, TNextCtx, TNextInput, TNextResult>,
@@ -132,8 +132,77 @@ where
     }
 
     // TODO: Allow multiple or error if defined multiple times?
-    pub fn setup(mut self, func: impl FnOnce(&mut State, ProcedureMeta) + 'static) -> Self {
+    pub fn setup(mut self, func: impl FnOnce(&mut State, SetupMeta) + 'static) -> Self {
         self.setup = Some(Box::new(func));
         self
     }
 }
+
+impl<TError, TCtx, TInput, T, S>
+    Middleware<
+        TError,
+        TCtx,
+        TInput,
+        crate::Stream<BoxStream<'static, Result<T, TError>>>,
+        TCtx,
+        TInput,
+        crate::Stream<S>,
+    >
+where
+    TError: Send + 'static,
+    TCtx: 'static,
+    TInput: 'static,
+    T: Send + 'static,
+    S: Stream<Item = Result<T, TError>> + Send + 'static,
+{
+    /// Create a middleware which runs for every item yielded by a [`subscription`](crate::ProcedureBuilder::subscription) (or a query or mutation returning a [`Stream`](crate::Stream)).
+    ///
+    /// `func` is called each time the procedure is executed and returns the handler for each item.
+    /// The handler can modify the item, replace it with an error or return `None` to skip it.
+    ///
+    /// ```rust
+    /// # use rspc::{middleware::Middleware, Procedure};
+    /// # #[derive(Debug, serde::Serialize, specta::Type)]
+    /// # enum Error { Unauthorized }
+    /// # impl rspc::Error for Error {
+    /// #     fn into_procedure_error(self) -> rspc::ProcedureError {
+    /// #         rspc::ResolverError::new(self, None::<std::io::Error>).into()
+    /// #     }
+    /// # }
+    /// # struct Ctx { user_id: u32 }
+    /// #[derive(Clone, serde::Serialize, specta::Type)]
+    /// struct Message { to: u32 }
+    ///
+    /// Procedure::<Ctx, _, _>::builder::<Error>()
+    ///     .with(Middleware::stream(|ctx: &Ctx, _meta| {
+    ///         let user_id = ctx.user_id;
+    ///         // Only forward messages the user is allowed to see
+    ///         move |msg: Result<Message, Error>| async move {
+    ///             msg.map(|msg| (msg.to == user_id).then_some(msg)).transpose()
+    ///         }
+    ///     }))
+    ///     .subscription(|_, _: ()| async move {
+    ///         Ok(futures::stream::iter([Ok(Message { to: 1 })]))
+    ///     });
+    /// ```
+    pub fn stream<M, F>(func: impl Fn(&TCtx, ProcedureMeta) -> M + Send + Sync + 'static) -> Self
+    where
+        M: FnMut(Result<T, TError>) -> F + Send + 'static,
+        F: Future<Output = Option<Result<T, TError>>> + Send + 'static,
+    {
+        Self {
+            setup: None,
+            inner: Box::new(move |next| {
+                Arc::new(move |ctx, input, meta| {
+                    let map = func(&ctx, meta.clone());
+                    let fut = next(ctx, input, meta);
+
+                    Box::pin(async move {
+                        let stream = fut.await?;
+                        Ok(crate::Stream(stream.0.then(map).filter_map(ready).boxed()))
+                    })
+                })
+            }),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    any::{type_name, Any},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+
+use rspc_procedure::{DowncastError, ProcedureError};
+
+use crate::ProcedureMeta;
+
+pub(crate) type RouterMiddlewareFuture =
+    Pin<Box<dyn Future<Output = Result<RouterOutput, ProcedureError>> + Send + 'static>>;
+
+pub(crate) type RouterMiddlewareHandler<TCtx> =
+    Arc<dyn Fn(TCtx, RouterNext<TCtx>) -> RouterMiddlewareFuture + Send + Sync + 'static>;
+
+/// The result of a procedure with the type erased.
+///
+/// This is returned from [`RouterNext::exec`] and must be returned by the middleware so the procedure can produce it's response.
+pub struct RouterOutput(pub(crate) Box<dyn Any + Send>);
+
+// Used as the source type of the [`DowncastError`] when a middleware returns a [`RouterOutput`] which doesn't belong to the procedure being executed.
+const FOREIGN_ROUTER_OUTPUT: &str = "`RouterOutput` not returned by this procedure's `next.exec`";
+
+impl RouterOutput {
+    /// Take the result of the procedure back out.
+    ///
+    /// This fails if the middleware returned the [`RouterOutput`] of a different procedure.
+    pub(crate) fn downcast<T: 'static>(self) -> Result<T, ProcedureError> {
+        self.0
+            .downcast::<T>()
+            .map(|v| *v)
+            .map_err(|_| DowncastError::new(Some(FOREIGN_ROUTER_OUTPUT), type_name::<T>()).into())
+    }
+}
+
+impl fmt::Debug for RouterOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RouterOutput").finish()
+    }
+}
+
+/// The next layer of a middleware registered with [`Router::with`](crate::Router::with).
+///
+/// Unlike [`Next`](super::Next) the input and result of the procedure are type-erased as the middleware applies to many procedures.
+pub struct RouterNext<TCtx> {
+    pub(crate) meta: ProcedureMeta,
+    pub(crate) next: Box<dyn FnOnce(TCtx) -> RouterMiddlewareFuture + Send>,
+}
+
+impl<TCtx> fmt::Debug for RouterNext<TCtx> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RouterNext")
+            .field("meta", &self.meta)
+            .finish()
+    }
+}
+
+impl<TCtx> RouterNext<TCtx> {
+    pub fn meta(&self) -> ProcedureMeta {
+        self.meta.clone()
+    }
+
+    pub async fn exec(self, ctx: TCtx) -> Result<RouterOutput, ProcedureError> {
+        (self.next)(ctx).await
+    }
+}
+
+/// Execute the router middleware starting at `index` with `handler` being the procedure itself.
+pub(crate) fn exec_router_middleware<TCtx: 'static>(
+    middleware: Arc<[RouterMiddlewareHandler<TCtx>]>,
+    index: usize,
+    meta: ProcedureMeta,
+    ctx: TCtx,
+    handler: Box<dyn FnOnce(TCtx) -> RouterMiddlewareFuture + Send>,
+) -> RouterMiddlewareFuture {
+    match middleware.get(index).cloned() {
+        Some(mw) => mw(
+            ctx,
+            RouterNext {
+                meta: meta.clone(),
+                next: Box::new(move |ctx| {
+                    exec_router_middleware(middleware, index + 1, meta, ctx, handler)
+                }),
+            },
+        ),
+        None => handler(ctx),
+    }
+}
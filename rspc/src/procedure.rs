@@ -22,25 +22,89 @@ mod resolver_output;
 
 pub use builder::ProcedureBuilder;
 pub use erased::ErasedProcedure;
-pub use meta::ProcedureMeta;
+pub use meta::{ProcedureMeta, SetupMeta};
 pub use resolver_input::ResolverInput;
 pub use resolver_output::ResolverOutput;
 
-use std::{borrow::Cow, marker::PhantomData, panic::Location, sync::Arc};
+use std::{any::Any, borrow::Cow, fmt, marker::PhantomData, panic::Location, sync::Arc};
 
 use futures_util::{FutureExt, TryStreamExt};
 
-use specta::{datatype::DataType, Generics, Type};
+use specta::{
+    datatype::{DataType, DeprecatedType},
+    Generics, Type,
+};
 
-use crate::{Error, Extension, ProcedureKind, State};
+use crate::{
+    middleware::{exec_router_middleware, RouterOutput},
+    Error, Extension, ProcedureKind, State,
+};
 
+/// The type information of a single procedure.
+///
+/// This can be accessed by walking [`Types::procedures`](crate::Types::procedures).
 #[derive(Clone)]
-pub(crate) struct ProcedureType {
+pub struct ProcedureType {
     pub(crate) kind: ProcedureKind,
     pub(crate) input: DataType,
     pub(crate) output: DataType,
     pub(crate) error: DataType,
     pub(crate) location: Location<'static>,
+    pub(crate) description: Cow<'static, str>,
+    pub(crate) deprecated: Option<DeprecatedType>,
+    pub(crate) metadata: Vec<Arc<dyn Any + Send + Sync>>,
+}
+
+impl ProcedureType {
+    pub fn kind(&self) -> ProcedureKind {
+        self.kind
+    }
+
+    pub fn input(&self) -> &DataType {
+        &self.input
+    }
+
+    pub fn output(&self) -> &DataType {
+        &self.output
+    }
+
+    pub fn error(&self) -> &DataType {
+        &self.error
+    }
+
+    /// The location in the source code where the procedure was defined.
+    pub fn location(&self) -> Location<'static> {
+        self.location
+    }
+
+    /// The description set with [`ProcedureBuilder::description`].
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Set if the procedure was marked deprecated with [`ProcedureBuilder::deprecated`].
+    pub fn deprecated(&self) -> Option<&DeprecatedType> {
+        self.deprecated.as_ref()
+    }
+
+    /// Get a value of type `T` attached with [`Procedure::meta`].
+    pub fn metadata<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.metadata.iter().find_map(|v| v.downcast_ref::<T>())
+    }
+}
+
+impl fmt::Debug for ProcedureType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProcedureType")
+            .field("kind", &self.kind)
+            .field("input", &self.input)
+            .field("output", &self.output)
+            .field("error", &self.error)
+            .field("location", &self.location)
+            .field("description", &self.description)
+            .field("deprecated", &self.deprecated)
+            .finish()
+    }
 }
 
 /// Represents a single operations on the server that can be executed.
@@ -49,7 +113,7 @@ pub(crate) struct ProcedureType {
 ///
 pub struct Procedure<TCtx, TInput, TResult> {
     pub(crate) build:
-        Box<dyn FnOnce(Vec<Box<dyn FnOnce(&mut State, ProcedureMeta)>>) -> ErasedProcedure<TCtx>>,
+        Box<dyn FnOnce(Vec<Box<dyn FnOnce(&mut State, SetupMeta)>>) -> ErasedProcedure<TCtx>>,
     pub(crate) phantom: PhantomData<(TInput, TResult)>,
 }
 
@@ -69,55 +133,69 @@ impl<TCtx, TInput, TOutput> Procedure<TCtx, TInput, TOutput> {
     {
         let location = Location::caller().clone();
         ProcedureBuilder {
-            build: Box::new(move |kind, setup, handler| {
-                ErasedProcedure {
-                    kind,
-                    setup: setup
-                        .into_iter()
-                        .map(|setup| {
-                            let v: Box<dyn FnOnce(&mut State)> =
-                                Box::new(move |state: &mut State| {
-                                    let key: Cow<'static, str> = "todo".to_string().into(); // TODO: Work this out properly
-                                    let meta = ProcedureMeta::new(
-                                        key.into(),
-                                        kind,
-                                        Arc::new(State::default()), // TODO: Can we configure a panic instead of this!
-                                    );
-                                    setup(state, meta);
-                                });
-                            v
-                        })
-                        .collect::<Vec<_>>(),
-                    location,
-                    inner: Box::new(move |state, types| {
-                        let key: Cow<'static, str> = "todo".to_string().into(); // TODO: Work this out properly
-                        let meta = ProcedureMeta::new(key.clone(), kind, state);
-
-                        (
-                            rspc_procedure::Procedure::new(move |ctx, input| {
-                                TOutput::into_procedure_stream(
-                                    handler(
-                                        ctx,
-                                        TInput::from_input(input).unwrap(), // TODO: Error handling
-                                        meta.clone(),
-                                    )
-                                    .into_stream()
-                                    .map_ok(|v| v.into_stream())
-                                    .map_err(|err| err.into_procedure_error())
-                                    .try_flatten()
-                                    .into_stream(),
+            build: Box::new(move |kind, setup, handler| ErasedProcedure {
+                kind,
+                setup,
+                location,
+                middleware: Vec::new(),
+                inner: Box::new(move |meta, middleware, types| {
+                    let handler = handler.clone();
+                    let middleware: Arc<[_]> = middleware.into();
+
+                    (
+                        rspc_procedure::Procedure::new(kind.into(), move |ctx, input| {
+                            let input = match TInput::from_input(input) {
+                                Ok(input) => input,
+                                Err(err) => return err.into(),
+                            };
+
+                            if middleware.is_empty() {
+                                return TOutput::into_procedure_stream(
+                                    handler(ctx, input, meta.clone())
+                                        .into_stream()
+                                        .map_ok(|v| v.into_stream())
+                                        .map_err(|err| err.into_procedure_error())
+                                        .try_flatten()
+                                        .into_stream(),
+                                );
+                            }
+
+                            let handler = handler.clone();
+                            let inner_meta = meta.clone();
+                            TOutput::into_procedure_stream(
+                                exec_router_middleware(
+                                    middleware.clone(),
+                                    0,
+                                    meta.clone(),
+                                    ctx,
+                                    Box::new(move |ctx| {
+                                        Box::pin(async move {
+                                            handler(ctx, input, inner_meta)
+                                                .await
+                                                .map(|v| RouterOutput(Box::new(v)))
+                                                .map_err(|err| err.into_procedure_error())
+                                        })
+                                    }),
                                 )
-                            }),
-                            ProcedureType {
-                                kind,
-                                location,
-                                input: TInput::data_type(types),
-                                output: TOutput::data_type(types),
-                                error: <TError as Type>::reference(types, &[]).inner,
-                            },
-                        )
-                    }),
-                }
+                                .map(|result| result.and_then(RouterOutput::downcast::<TOutput>))
+                                .into_stream()
+                                .map_ok(|v| v.into_stream())
+                                .try_flatten()
+                                .into_stream(),
+                            )
+                        }),
+                        ProcedureType {
+                            kind,
+                            location,
+                            input: TInput::data_type(types),
+                            output: TOutput::data_type(types),
+                            error: <TError as Type>::reference(types, &[]).inner,
+                            description: Cow::Borrowed(""),
+                            deprecated: None,
+                            metadata: Vec::new(),
+                        },
+                    )
+                }),
             }),
             phantom: PhantomData,
         }
@@ -138,6 +216,26 @@ impl<TCtx, TInput, TOutput> Procedure<TCtx, TInput, TOutput> {
         }
     }
 
+    /// Attach metadata to the procedure.
+    ///
+    /// This is available to integrations using [`rspc_procedure::Procedure::metadata`] after the router is built and when walking the procedures with [`ProcedureType::metadata`].
+    pub fn meta<T: Send + Sync + 'static>(self, value: T) -> Self
+    where
+        TCtx: 'static,
+    {
+        let value = Arc::new(value);
+        Procedure {
+            build: Box::new(move |setups| {
+                (self.build)(setups).map(move |procedure, ty| {
+                    ty.metadata.retain(|v| !v.is::<T>());
+                    ty.metadata.push(value.clone());
+                    procedure.with_metadata_arc(value.clone())
+                })
+            }),
+            phantom: PhantomData,
+        }
+    }
+
     // TODO: Expose all fields
 
     // TODO: Make `pub`
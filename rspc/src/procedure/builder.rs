@@ -1,21 +1,22 @@
-use std::{fmt, future::Future, marker::PhantomData, sync::Arc};
+use std::{borrow::Cow, fmt, future::Future, marker::PhantomData, sync::Arc};
 
 use crate::{
     middleware::{IntoMiddleware, MiddlewareHandler},
     Error, Procedure,
 };
 
-use super::{ErasedProcedure, ProcedureKind, ProcedureMeta};
+use super::{ErasedProcedure, ProcedureKind, SetupMeta};
 
 use futures_util::{FutureExt, Stream};
 use rspc_procedure::State;
+use specta::datatype::DeprecatedType;
 
 // TODO: Document the generics like `Middleware`. What order should they be in?
 pub struct ProcedureBuilder<TError, TBaseCtx, TCtx, TBaseInput, TInput, TBaseResult, TResult> {
     pub(crate) build: Box<
         dyn FnOnce(
             ProcedureKind,
-            Vec<Box<dyn FnOnce(&mut State, ProcedureMeta) + 'static>>,
+            Vec<Box<dyn FnOnce(&mut State, SetupMeta) + 'static>>,
             MiddlewareHandler<TError, TCtx, TInput, TResult>,
         ) -> ErasedProcedure<TBaseCtx>,
     >,
@@ -55,7 +56,7 @@ where
         mw.build(self)
     }
 
-    pub fn setup(self, func: impl FnOnce(&mut State, ProcedureMeta) + 'static) -> Self {
+    pub fn setup(self, func: impl FnOnce(&mut State, SetupMeta) + 'static) -> Self {
         Self {
             build: Box::new(|ty, mut setups, handler| {
                 setups.push(Box::new(func));
@@ -65,6 +66,41 @@ where
         }
     }
 
+    /// Attach a human-readable description to the procedure.
+    ///
+    /// This is exported into the bindings (Eg. as JSDoc with [`Typescript`](crate::Typescript)).
+    pub fn description(self, description: impl Into<Cow<'static, str>>) -> Self {
+        let description = description.into();
+        Self {
+            build: Box::new(|kind, setups, handler| {
+                (self.build)(kind, setups, handler).map(move |procedure, ty| {
+                    ty.description = description.clone();
+                    procedure
+                })
+            }),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Mark the procedure as deprecated with a reason.
+    ///
+    /// This is exported into the bindings (Eg. as `@deprecated` with [`Typescript`](crate::Typescript)).
+    pub fn deprecated(self, reason: impl Into<Cow<'static, str>>) -> Self {
+        let note = reason.into();
+        Self {
+            build: Box::new(|kind, setups, handler| {
+                (self.build)(kind, setups, handler).map(move |procedure, ty| {
+                    ty.deprecated = Some(DeprecatedType::DeprecatedWithSince {
+                        since: None,
+                        note: note.clone(),
+                    });
+                    procedure
+                })
+            }),
+            phantom: PhantomData,
+        }
+    }
+
     pub fn query<F: Future<Output = Result<TResult, TError>> + Send + 'static>(
         self,
         handler: impl Fn(TCtx, TInput) -> F + Send + Sync + 'static,
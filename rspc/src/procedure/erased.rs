@@ -1,16 +1,24 @@
-use std::{panic::Location, sync::Arc};
+use std::panic::Location;
 
 use specta::TypeCollection;
 
-use crate::{procedure::ProcedureType, ProcedureKind, State};
+use crate::{
+    middleware::RouterMiddlewareHandler, procedure::ProcedureType, ProcedureKind, ProcedureMeta,
+    SetupMeta, State,
+};
 
 pub struct ErasedProcedure<TCtx> {
-    pub(crate) setup: Vec<Box<dyn FnOnce(&mut State) + 'static>>,
+    pub(crate) setup: Vec<Box<dyn FnOnce(&mut State, SetupMeta) + 'static>>,
     pub(crate) location: Location<'static>,
     pub(crate) kind: ProcedureKind,
+    // Middleware from `Router::with` which wrap this procedure. The outermost is first.
+    pub(crate) middleware: Vec<RouterMiddlewareHandler<TCtx>>,
+    // Given the [`ProcedureMeta`] for the final (flattened) key of the procedure within the router.
+    // This is called once for the procedure and once for each alias registered with `Router::alias`.
     pub(crate) inner: Box<
-        dyn FnOnce(
-            Arc<State>,
+        dyn Fn(
+            ProcedureMeta,
+            Vec<RouterMiddlewareHandler<TCtx>>,
             &mut TypeCollection,
         ) -> (rspc_procedure::Procedure<TCtx>, ProcedureType),
     >,
@@ -19,6 +27,27 @@ pub struct ErasedProcedure<TCtx> {
 // TODO: `Debug`, `PartialEq`, `Eq`, `Hash`
 
 impl<TCtx> ErasedProcedure<TCtx> {
+    /// Modify the procedure and it's type information once it has been built.
+    pub(crate) fn map(
+        mut self,
+        func: impl Fn(
+                rspc_procedure::Procedure<TCtx>,
+                &mut ProcedureType,
+            ) -> rspc_procedure::Procedure<TCtx>
+            + 'static,
+    ) -> Self
+    where
+        TCtx: 'static,
+    {
+        let inner = self.inner;
+        self.inner = Box::new(move |meta, middleware, types| {
+            let (procedure, mut ty) = inner(meta, middleware, types);
+            let procedure = func(procedure, &mut ty);
+            (procedure, ty)
+        });
+        self
+    }
+
     // TODO: Expose all fields
 
     // TODO: Make `pub`
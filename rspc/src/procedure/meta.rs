@@ -30,20 +30,41 @@ enum ProcedureName {
 pub struct ProcedureMeta {
     name: ProcedureName,
     kind: ProcedureKind,
+    // The name of the procedure this is an alias of. Set when called through `Router::alias`.
+    alias_of: Option<ProcedureName>,
     state: Arc<State>,
 }
 
 impl ProcedureMeta {
     pub(crate) fn new(name: Cow<'static, str>, kind: ProcedureKind, state: Arc<State>) -> Self {
         Self {
-            name: ProcedureName::Dynamic(Arc::new(name.into_owned())),
+            name: name.into(),
             kind,
+            alias_of: None,
             state,
         }
     }
+
+    /// Mark this procedure as being called through an alias of `target`.
+    pub(crate) fn with_alias_of(mut self, target: Cow<'static, str>) -> Self {
+        self.alias_of = Some(target.into());
+        self
+    }
+}
+
+impl From<Cow<'static, str>> for ProcedureName {
+    fn from(name: Cow<'static, str>) -> Self {
+        match name {
+            Cow::Borrowed(name) => Self::Static(name),
+            Cow::Owned(name) => Self::Dynamic(Arc::new(name)),
+        }
+    }
 }
 
 impl ProcedureMeta {
+    /// The full path of the procedure within the router.
+    ///
+    /// Nested routers are joined by a `.`, Eg. `users.get`.
     pub fn name(&self) -> &str {
         match &self.name {
             ProcedureName::Static(name) => name,
@@ -55,7 +76,50 @@ impl ProcedureMeta {
         self.kind
     }
 
+    /// If the procedure was called through an alias registered with [`Router::alias`](crate::Router::alias) this is the name of the procedure it points to.
+    ///
+    /// In this case [`ProcedureMeta::name`] is the deprecated name the client used.
+    pub fn alias_of(&self) -> Option<&str> {
+        self.alias_of.as_ref().map(|name| match name {
+            ProcedureName::Static(name) => *name,
+            ProcedureName::Dynamic(name) => name.as_str(),
+        })
+    }
+
+    /// The [`State`] of the router this procedure is mounted on.
     pub fn state(&self) -> &Arc<State> {
         &self.state
     }
 }
+
+/// Information about the procedure given to setup functions.
+///
+/// This is like [`ProcedureMeta`] but without the [`State`] as it is still being built. Use the `&mut State` argument of the setup function instead.
+#[derive(Debug, Clone)]
+pub struct SetupMeta {
+    name: ProcedureName,
+    kind: ProcedureKind,
+}
+
+impl SetupMeta {
+    pub(crate) fn new(name: Cow<'static, str>, kind: ProcedureKind) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+        }
+    }
+
+    /// The full path of the procedure within the router.
+    ///
+    /// Nested routers are joined by a `.`, Eg. `users.get`.
+    pub fn name(&self) -> &str {
+        match &self.name {
+            ProcedureName::Static(name) => name,
+            ProcedureName::Dynamic(name) => name.as_str(),
+        }
+    }
+
+    pub fn kind(&self) -> ProcedureKind {
+        self.kind
+    }
+}
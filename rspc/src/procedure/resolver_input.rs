@@ -49,7 +49,14 @@ impl<T: DeserializeOwned + Type + Send + 'static> ResolverInput for T {
         T::inline(types, specta::Generics::Definition)
     }
 
-    fn from_input(input: rspc_procedure::DynInput) -> Result<Self, rspc_procedure::ProcedureError> {
+    fn from_input(
+        mut input: rspc_procedure::DynInput,
+    ) -> Result<Self, rspc_procedure::ProcedureError> {
+        // When called in-process (Eg. with `Caller`) the value is passed through without deserializing it.
+        if let Ok(value) = input.value::<Option<T>>() {
+            return Ok(value.take().expect("unreachable"));
+        }
+
         Ok(input.deserialize()?)
     }
 }
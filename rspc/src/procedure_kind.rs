@@ -19,3 +19,13 @@ impl fmt::Display for ProcedureKind {
         }
     }
 }
+
+impl From<ProcedureKind> for rspc_procedure::ProcedureKind {
+    fn from(kind: ProcedureKind) -> Self {
+        match kind {
+            ProcedureKind::Query => Self::Query,
+            ProcedureKind::Mutation => Self::Mutation,
+            ProcedureKind::Subscription => Self::Subscription,
+        }
+    }
+}
@@ -1,32 +1,50 @@
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, HashMap},
+    collections::{btree_map::Entry, BTreeMap, HashMap},
     fmt,
+    future::Future,
     panic::Location,
     sync::Arc,
 };
 
-use specta::TypeCollection;
+use specta::{datatype::DeprecatedType, TypeCollection};
 
-use rspc_procedure::Procedures;
+use rspc_procedure::{ProcedureError, Procedures};
 
-use crate::{procedure::ErasedProcedure, types::TypesOrType, ProcedureKind, State, Types};
+use crate::{
+    middleware::{RouterMiddlewareHandler, RouterNext, RouterOutput},
+    procedure::{ErasedProcedure, ProcedureType},
+    types::TypesOrType,
+    ProcedureKind, ProcedureMeta, SetupMeta, State, Types,
+};
+
+// The path of a procedure within the router. Eg. `["users", "get"]`.
+type ProcedureKey = Vec<Cow<'static, str>>;
+// The key of the procedure an alias points to and where the alias was registered.
+type AliasTarget = (ProcedureKey, Location<'static>);
 
 /// TODO: Examples exporting types and with `rspc_axum`
 pub struct Router<TCtx = ()> {
     setup: Vec<Box<dyn FnOnce(&mut State) + 'static>>,
+    middleware: Vec<RouterMiddlewareHandler<TCtx>>,
     // TODO: Seal these once `rspc-legacy` is gone.
     pub(crate) types: TypeCollection,
     pub(crate) procedures: BTreeMap<Vec<Cow<'static, str>>, ErasedProcedure<TCtx>>,
-    errors: Vec<DuplicateProcedureKeyError>,
+    // Where each nested router was mounted. This is used for the Typescript source maps.
+    nested: BTreeMap<ProcedureKey, Location<'static>>,
+    aliases: BTreeMap<ProcedureKey, AliasTarget>,
+    errors: Vec<BuildError>,
 }
 
 impl<TCtx> Default for Router<TCtx> {
     fn default() -> Self {
         Self {
             setup: Default::default(),
+            middleware: Default::default(),
             types: Default::default(),
             procedures: Default::default(),
+            nested: Default::default(),
+            aliases: Default::default(),
             errors: vec![],
         }
     }
@@ -46,43 +64,118 @@ impl<TCtx> Router<TCtx> {
         let key = key.into();
 
         if let Some((_, original)) = self.procedures.iter().find(|(k, _)| k[0] == key) {
-            self.errors.push(DuplicateProcedureKeyError {
-                path: vec![key],
-                original: original.location,
-                duplicate: Location::caller().clone(),
-            });
+            self.errors.push(BuildError::DuplicateProcedureKey(
+                DuplicateProcedureKeyError {
+                    path: vec![key],
+                    original: original.location,
+                    duplicate: Location::caller().clone(),
+                },
+            ));
+        } else {
+            self.procedures.insert(vec![key], procedure.into());
+        }
+
+        self
+    }
+
+    /// Make the procedure at `target` also available under `alias`.
+    ///
+    /// This allows renaming a procedure without breaking clients still using the old key.
+    /// Both keys are the full path within this router, with nested routers separated by a `.` (Eg. `users.get`). The target is resolved when the router is built, so it may be added after this is called.
+    ///
+    /// The alias is exported as deprecated in the bindings and [`ProcedureMeta::alias_of`] is set when it's called so it's usage can be tracked.
+    ///
+    /// ```rust
+    /// # use rspc::Router;
+    /// let router = <Router>::new().alias("getUser", "users.get");
+    /// ```
+    #[track_caller]
+    pub fn alias(
+        mut self,
+        alias: impl Into<Cow<'static, str>>,
+        target: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        let alias = split_key(alias.into());
+        let target = split_key(target.into());
+
+        if let Some((_, original)) = self.aliases.get(&alias) {
+            self.errors.push(BuildError::DuplicateProcedureKey(
+                DuplicateProcedureKeyError {
+                    path: alias,
+                    original: *original,
+                    duplicate: *Location::caller(),
+                },
+            ));
         } else {
-            let mut procedure = procedure.into();
-            self.setup.extend(procedure.setup.drain(..));
-            self.procedures.insert(vec![key], procedure);
+            self.aliases.insert(alias, (target, *Location::caller()));
         }
 
         self
     }
 
-    // TODO: Document the order this is run in for `build`
+    /// Register a function to modify the [`State`] when the router is built.
+    ///
+    /// When building the router setup functions are run in the following order:
+    ///  - Router setup functions in the order they were registered. Setup functions from routers added with [`Router::nest`] or [`Router::merge`] are appended when they are added.
+    ///  - Procedure setup functions (Eg. [`ProcedureBuilder::setup`](crate::ProcedureBuilder::setup), [`Middleware::setup`](crate::middleware::Middleware::setup) or [`Extension::setup`](crate::Extension::setup)), in the order of the procedure keys. Within a single procedure they run in the order they were attached.
+    ///
+    /// After all setup functions have run any type marked with [`State::require`] must be present in the state or the build will fail.
     pub fn setup(mut self, func: impl FnOnce(&mut State) + 'static) -> Self {
         self.setup.push(Box::new(func));
         self
     }
 
+    /// Apply a middleware to every procedure in this router.
+    ///
+    /// This includes procedures added with [`Router::nest`] and [`Router::merge`] regardless of whether they are added before or after this is called.
+    ///
+    /// As this middleware applies to many procedures the input and result are type-erased.
+    /// The middleware is run before any middleware registered with [`ProcedureBuilder::with`](crate::ProcedureBuilder::with) and will be given the [`ProcedureMeta`] of the procedure being executed.
+    /// If called multiple times, the first middleware registered will be the outermost.
+    ///
+    /// ```rust
+    /// # use rspc::{middleware::RouterNext, Router};
+    /// let router = <Router>::new().with(|ctx: (), next: RouterNext<()>| async move {
+    ///     println!("Executing {}", next.meta().name());
+    ///     next.exec(ctx).await
+    /// });
+    /// ```
+    pub fn with<F>(
+        mut self,
+        mw: impl Fn(TCtx, RouterNext<TCtx>) -> F + Send + Sync + 'static,
+    ) -> Self
+    where
+        F: Future<Output = Result<RouterOutput, ProcedureError>> + Send + 'static,
+    {
+        self.middleware
+            .push(Arc::new(move |ctx, next| Box::pin(mw(ctx, next))));
+        self
+    }
+
     #[track_caller]
     pub fn nest(mut self, prefix: impl Into<Cow<'static, str>>, mut other: Self) -> Self {
         let prefix = prefix.into();
+        other.apply_middleware();
 
         if let Some((_, original)) = self.procedures.iter().find(|(k, _)| k[0] == prefix) {
-            self.errors.push(DuplicateProcedureKeyError {
-                path: vec![prefix],
-                original: original.location,
-                duplicate: Location::caller().clone(),
-            });
+            self.errors.push(BuildError::DuplicateProcedureKey(
+                DuplicateProcedureKeyError {
+                    path: vec![prefix],
+                    original: original.location,
+                    duplicate: Location::caller().clone(),
+                },
+            ));
         } else {
             self.setup.append(&mut other.setup);
-            self.errors.extend(other.errors.into_iter().map(|e| {
-                let mut path = vec![prefix.clone()];
-                path.extend(e.path);
-                DuplicateProcedureKeyError { path, ..e }
-            }));
+            self.errors
+                .extend(other.errors.into_iter().map(|e| match e {
+                    BuildError::DuplicateProcedureKey(e) => {
+                        let mut path = vec![prefix.clone()];
+                        path.extend(e.path);
+                        BuildError::DuplicateProcedureKey(DuplicateProcedureKeyError { path, ..e })
+                    }
+                    e => e,
+                }));
             self.types.extend(other.types);
             self.procedures
                 .extend(other.procedures.into_iter().map(|(k, v)| {
@@ -90,6 +183,20 @@ impl<TCtx> Router<TCtx> {
                     key.extend(k);
                     (key, v)
                 }));
+            self.nested.extend(other.nested.into_iter().map(|(k, v)| {
+                let mut key = vec![prefix.clone()];
+                key.extend(k);
+                (key, v)
+            }));
+            self.aliases
+                .extend(other.aliases.into_iter().map(|(k, (target, location))| {
+                    let mut key = vec![prefix.clone()];
+                    key.extend(k);
+                    let mut target_key = vec![prefix.clone()];
+                    target_key.extend(target);
+                    (key, (target_key, location))
+                }));
+            self.nested.insert(vec![prefix], *Location::caller());
         }
 
         self
@@ -97,84 +204,194 @@ impl<TCtx> Router<TCtx> {
 
     #[track_caller]
     pub fn merge(mut self, mut other: Self) -> Self {
+        other.apply_middleware();
         for (k, original) in other.procedures.iter() {
             if let Some(new) = self.procedures.get(k) {
-                self.errors.push(DuplicateProcedureKeyError {
-                    path: k.clone(),
-                    original: original.location,
-                    duplicate: new.location,
-                });
+                self.errors.push(BuildError::DuplicateProcedureKey(
+                    DuplicateProcedureKeyError {
+                        path: k.clone(),
+                        original: original.location,
+                        duplicate: new.location,
+                    },
+                ));
+            }
+        }
+
+        // Aliases overlapping a procedure are caught when the router is built but two aliases with the same key would overwrite each other.
+        for (k, (target, duplicate)) in other.aliases {
+            match self.aliases.entry(k) {
+                Entry::Occupied(entry) => self.errors.push(BuildError::DuplicateProcedureKey(
+                    DuplicateProcedureKeyError {
+                        path: entry.key().clone(),
+                        original: entry.get().1,
+                        duplicate,
+                    },
+                )),
+                Entry::Vacant(entry) => {
+                    entry.insert((target, duplicate));
+                }
             }
         }
 
         self.setup.append(&mut other.setup);
         self.procedures.extend(other.procedures.into_iter());
+        self.nested.extend(other.nested);
         self.types.extend(other.types);
         self.errors.extend(other.errors);
 
         self
     }
 
-    pub fn build(self) -> Result<(Procedures<TCtx>, Types), Vec<DuplicateProcedureKeyError>> {
-        self.build_with_state_inner(State::default())
+    /// Build the router into a set of [`Procedures`] and it's [`Types`].
+    ///
+    /// This is equivalent to [`Router::build_with_state`] with an empty [`State`].
+    pub fn build(self) -> Result<(Procedures<TCtx>, Types), Vec<BuildError>> {
+        self.build_with_state(State::default())
     }
 
-    // pub fn build_with_state(
-    //     self,
-    //     state: State,
-    // ) -> Result<(Procedures<TCtx>, Types), Vec<DuplicateProcedureKeyError>> {
-    //     self.build_with_state_inner(state)
-    // }
-
-    fn build_with_state_inner(
+    /// Build the router using a pre-built [`State`].
+    ///
+    /// This is useful for providing things like a database pool or configuration without capturing them in a [`Router::setup`] function.
+    /// All setup functions are run on top of the provided state. Refer to [`Router::setup`] for the order they are run in.
+    pub fn build_with_state(
         mut self,
         mut state: State,
-    ) -> Result<(Procedures<TCtx>, Types), Vec<DuplicateProcedureKeyError>> {
+    ) -> Result<(Procedures<TCtx>, Types), Vec<BuildError>> {
+        self.validate_aliases();
         if self.errors.len() > 0 {
             return Err(self.errors);
         }
+        self.apply_middleware();
+
+        let mut aliases = BTreeMap::<_, Vec<_>>::new();
+        for (alias, (target, location)) in self.aliases {
+            aliases.entry(target).or_default().push((alias, location));
+        }
 
         for setup in self.setup {
             setup(&mut state);
         }
-        let state = Arc::new(state);
 
-        let mut procedure_types = BTreeMap::new();
         let procedures = self
             .procedures
             .into_iter()
-            .map(|(key, p)| {
-                let (procedure, ty) = (p.inner)(state.clone(), &mut self.types);
-
-                let mut current = &mut procedure_types;
-                // TODO: if `key.len()` is `0` we might run into issues here. It shouldn't but probs worth protecting.
-                for part in &key[..(key.len() - 1)] {
-                    let a = current
-                        .entry(part.clone())
-                        .or_insert_with(|| TypesOrType::Types(Default::default()));
-                    match a {
-                        TypesOrType::Type(_) => unreachable!(), // TODO: Confirm this is unreachable
-                        TypesOrType::Types(map) => current = map,
-                    }
+            .map(|(key, mut p)| {
+                let name = get_flattened_name(&key);
+                for setup in p.setup.drain(..) {
+                    setup(&mut state, SetupMeta::new(name.clone(), p.kind));
                 }
-                current.insert(key[key.len() - 1].clone(), TypesOrType::Type(ty));
-
-                (get_flattened_name(&key), procedure)
+                (key, name, p)
             })
-            .collect::<HashMap<_, _>>();
+            .collect::<Vec<_>>();
+
+        let missing = state
+            .missing()
+            .map(BuildError::MissingState)
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(missing);
+        }
+        let state = Arc::new(state);
+
+        let mut procedure_types = BTreeMap::new();
+        let mut built = HashMap::new();
+        for (key, name, p) in procedures {
+            for (alias, location) in aliases.remove(&key).unwrap_or_default() {
+                let alias_name = get_flattened_name(&alias);
+                let (procedure, mut ty) = (p.inner)(
+                    ProcedureMeta::new(alias_name.clone(), p.kind, state.clone())
+                        .with_alias_of(name.clone()),
+                    p.middleware.clone(),
+                    &mut self.types,
+                );
+                ty.location = location;
+                if ty.deprecated.is_none() {
+                    ty.deprecated = Some(DeprecatedType::DeprecatedWithSince {
+                        since: None,
+                        note: format!("Use `{name}` instead").into(),
+                    });
+                }
+
+                insert_type(&mut procedure_types, &alias, ty);
+                built.insert(alias_name, procedure);
+            }
+
+            let (procedure, ty) = (p.inner)(
+                ProcedureMeta::new(name.clone(), p.kind, state.clone()),
+                p.middleware,
+                &mut self.types,
+            );
+
+            insert_type(&mut procedure_types, &key, ty);
+            built.insert(name, procedure);
+        }
 
         Ok((
-            Procedures::new(procedures, state),
+            Procedures::new(built, state),
             // TODO: Get rid of this and have `rspc-tracing` mount it
             // .with_logger(|event| println!("{event:?}")),
             Types {
                 types: self.types,
                 procedures: procedure_types,
+                nested: self.nested,
             },
         ))
     }
 }
 
+impl<TCtx> Router<TCtx> {
+    // Ensure every alias points to a procedure and doesn't overlap with any other key.
+    fn validate_aliases(&mut self) {
+        for (alias, (target, location)) in &self.aliases {
+            let overlaps =
+                |k: &Vec<Cow<'static, str>>| k.starts_with(alias) || alias.starts_with(k);
+
+            if let Some((_, original)) = self.procedures.iter().find(|(k, _)| overlaps(k)) {
+                self.errors.push(BuildError::DuplicateProcedureKey(
+                    DuplicateProcedureKeyError {
+                        path: alias.clone(),
+                        original: original.location,
+                        duplicate: *location,
+                    },
+                ));
+            } else if let Some((_, (_, original))) = self
+                .aliases
+                .iter()
+                .find(|(k, _)| *k != alias && overlaps(k))
+            {
+                self.errors.push(BuildError::DuplicateProcedureKey(
+                    DuplicateProcedureKeyError {
+                        path: alias.clone(),
+                        original: *original,
+                        duplicate: *location,
+                    },
+                ));
+            } else if !self.procedures.contains_key(target) {
+                self.errors
+                    .push(BuildError::UnknownAliasTarget(UnknownAliasTargetError {
+                        alias: alias.clone(),
+                        target: target.clone(),
+                        location: *location,
+                    }));
+            }
+        }
+    }
+
+    // Move the router's middleware onto each of it's procedures so they are preserved when nesting or merging.
+    fn apply_middleware(&mut self) {
+        if self.middleware.is_empty() {
+            return;
+        }
+
+        for procedure in self.procedures.values_mut() {
+            procedure
+                .middleware
+                .splice(0..0, self.middleware.iter().cloned());
+        }
+        self.middleware.clear();
+    }
+}
+
 impl<TCtx> fmt::Debug for Router<TCtx> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let procedure_keys = |kind: ProcedureKind| {
@@ -206,6 +423,32 @@ impl<'a, TCtx> IntoIterator for &'a Router<TCtx> {
     }
 }
 
+fn split_key(key: Cow<'static, str>) -> Vec<Cow<'static, str>> {
+    match key {
+        Cow::Borrowed(key) => key.split('.').map(Cow::Borrowed).collect(),
+        Cow::Owned(key) => key.split('.').map(|s| s.to_string().into()).collect(),
+    }
+}
+
+fn insert_type(
+    procedure_types: &mut BTreeMap<Cow<'static, str>, TypesOrType>,
+    key: &[Cow<'static, str>],
+    ty: ProcedureType,
+) {
+    let mut current = procedure_types;
+    // TODO: if `key.len()` is `0` we might run into issues here. It shouldn't but probs worth protecting.
+    for part in &key[..(key.len() - 1)] {
+        let a = current
+            .entry(part.clone())
+            .or_insert_with(|| TypesOrType::Types(Default::default()));
+        match a {
+            TypesOrType::Type(_) => unreachable!(), // TODO: Confirm this is unreachable
+            TypesOrType::Types(map) => current = map,
+        }
+    }
+    current.insert(key[key.len() - 1].clone(), TypesOrType::Type(ty));
+}
+
 fn get_flattened_name(name: &Vec<Cow<'static, str>>) -> Cow<'static, str> {
     if name.len() == 1 {
         // By cloning we are ensuring we passthrough to the `Cow` to avoid cloning if this is a `&'static str`.
@@ -216,6 +459,37 @@ fn get_flattened_name(name: &Vec<Cow<'static, str>>) -> Cow<'static, str> {
     }
 }
 
+/// An error that occurred while building a [`Router`].
+pub enum BuildError {
+    /// Multiple procedures were registered with the same key.
+    DuplicateProcedureKey(DuplicateProcedureKeyError),
+    /// A type marked with [`State::require`] was not found in the [`State`] after running all setup functions.
+    MissingState(&'static str),
+    /// An alias registered with [`Router::alias`] points to a procedure which doesn't exist.
+    UnknownAliasTarget(UnknownAliasTargetError),
+}
+
+impl fmt::Debug for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateProcedureKey(err) => write!(f, "{err:?}"),
+            Self::UnknownAliasTarget(err) => write!(f, "{err:?}"),
+            Self::MissingState(ty) => writeln!(
+                f,
+                "Missing required state '{ty}'. It must be provided with `Router::setup` or `Router::build_with_state`."
+            ),
+        }
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for BuildError {}
+
 pub struct DuplicateProcedureKeyError {
     path: Vec<Cow<'static, str>>,
     original: Location<'static>,
@@ -245,3 +519,31 @@ impl fmt::Display for DuplicateProcedureKeyError {
 }
 
 impl std::error::Error for DuplicateProcedureKeyError {}
+
+pub struct UnknownAliasTargetError {
+    alias: Vec<Cow<'static, str>>,
+    target: Vec<Cow<'static, str>>,
+    location: Location<'static>,
+}
+
+impl fmt::Debug for UnknownAliasTargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Alias {:?} points to unknown procedure {:?}. Alias: {}:{}:{}",
+            self.alias,
+            self.target,
+            self.location.file(),
+            self.location.line(),
+            self.location.column()
+        )
+    }
+}
+
+impl fmt::Display for UnknownAliasTargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for UnknownAliasTargetError {}
@@ -1,4 +1,4 @@
-use std::{borrow::Cow, collections::BTreeMap, fmt};
+use std::{borrow::Cow, collections::BTreeMap, fmt, panic::Location};
 
 use specta::TypeCollection;
 
@@ -13,12 +13,20 @@ pub(crate) enum TypesOrType {
 pub struct Types {
     pub(crate) types: TypeCollection,
     pub(crate) procedures: BTreeMap<Cow<'static, str>, TypesOrType>,
+    /// The location each nested router was mounted with [`Router::nest`](crate::Router::nest).
+    pub(crate) nested: BTreeMap<Vec<Cow<'static, str>>, Location<'static>>,
 }
 
 impl fmt::Debug for Types {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Types")
-            // TODO: Finish this
+            .field(
+                "procedures",
+                &self
+                    .procedures()
+                    .map(|(path, _)| path.join("."))
+                    .collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
@@ -26,5 +34,34 @@ impl fmt::Debug for Types {
 // TODO: Traits
 
 impl Types {
-    // TODO: Expose inners for manual exporting logic
+    /// The [`TypeCollection`] containing every named type referenced by the procedures.
+    pub fn types(&self) -> &TypeCollection {
+        &self.types
+    }
+
+    /// Walk every procedure in key order.
+    ///
+    /// Each procedure is yielded with it's path within the router (Eg. `["users", "get"]` for `users.get`).
+    pub fn procedures(
+        &self,
+    ) -> impl Iterator<Item = (Vec<Cow<'static, str>>, &ProcedureType)> + '_ {
+        fn inner<'a>(
+            map: &'a BTreeMap<Cow<'static, str>, TypesOrType>,
+            path: &mut Vec<Cow<'static, str>>,
+            result: &mut Vec<(Vec<Cow<'static, str>>, &'a ProcedureType)>,
+        ) {
+            for (key, item) in map {
+                path.push(key.clone());
+                match item {
+                    TypesOrType::Type(ty) => result.push((path.clone(), ty)),
+                    TypesOrType::Types(map) => inner(map, path, result),
+                }
+                path.pop();
+            }
+        }
+
+        let mut result = Vec::new();
+        inner(&self.procedures, &mut Vec::new(), &mut result);
+        result.into_iter()
+    }
 }
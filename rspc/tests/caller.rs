@@ -0,0 +1,109 @@
+use futures::StreamExt;
+use rspc::{CallError, Caller, Procedure, ProcedureError, ResolverError, Router};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, PartialEq, Serialize, Type)]
+enum Error {
+    NotFound(u32),
+}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        ResolverError::new(self, None::<std::io::Error>).into()
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, Type)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+fn caller() -> Caller<()> {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "users.get",
+            Procedure::builder().query(|_, id: u32| async move {
+                match id {
+                    1 => Ok(User {
+                        id,
+                        name: "Oscar".into(),
+                    }),
+                    id => Err(Error::NotFound(id)),
+                }
+            }),
+        )
+        .procedure(
+            "users.create",
+            Procedure::builder().mutation(|_, user: User| async move { Ok::<_, Error>(user) }),
+        )
+        .procedure(
+            "count",
+            Procedure::builder().subscription(|_, to: u32| async move {
+                Ok::<_, Error>(rspc::Stream(futures::stream::iter(
+                    (0..to).map(Ok::<_, Error>),
+                )))
+            }),
+        )
+        .build()
+        .unwrap();
+
+    Caller::new(procedures)
+}
+
+#[test]
+fn typed_caller() {
+    let caller = caller();
+
+    futures::executor::block_on(async {
+        assert_eq!(
+            caller
+                .query::<User, Error>("users.get", (), 1u32)
+                .await
+                .unwrap(),
+            User {
+                id: 1,
+                name: "Oscar".into()
+            }
+        );
+
+        assert!(matches!(
+            caller.query::<User, Error>("users.get", (), 2u32).await,
+            Err(CallError::Resolver(Error::NotFound(2)))
+        ));
+
+        // The input is passed through without serializing it.
+        let user = User {
+            id: 5,
+            name: "Brendan".into(),
+        };
+        assert_eq!(
+            caller
+                .mutation::<User, Error>("users.create", (), user)
+                .await
+                .unwrap()
+                .name,
+            "Brendan"
+        );
+
+        assert_eq!(
+            caller
+                .subscription::<u32, Error>("count", (), 3u32)
+                .map(Result::unwrap)
+                .collect::<Vec<_>>()
+                .await,
+            vec![0, 1, 2]
+        );
+
+        // Calling a procedure as the wrong kind is the same as it not existing.
+        assert!(matches!(
+            caller.mutation::<User, Error>("users.get", (), 1u32).await,
+            Err(CallError::Procedure(ProcedureError::NotFound))
+        ));
+        assert!(matches!(
+            caller.query::<String, Error>("users.get", (), 1u32).await,
+            Err(CallError::Procedure(ProcedureError::Downcast(_)))
+        ));
+    });
+}
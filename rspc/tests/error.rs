@@ -0,0 +1,293 @@
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use rspc::{ErrorCode, LogMessage, PathSegment, Procedure, ProcedureError, ResolverError, Router};
+use rspc_procedure::DeserializeError;
+use serde_json::Value;
+
+#[derive(Debug, serde::Serialize, specta::Type)]
+enum Error {
+    Unauthorized,
+    Other,
+}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        let code = match self {
+            Self::Unauthorized => Some(ErrorCode::Unauthorized),
+            Self::Other => None,
+        };
+
+        let err = ResolverError::new(self, None::<std::io::Error>);
+        match code {
+            Some(code) => err.with_code(code),
+            None => err,
+        }
+        .into()
+    }
+}
+
+#[test]
+fn error_codes() {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "unauthorized",
+            Procedure::builder().query(|_, _: ()| async { Err::<(), _>(Error::Unauthorized) }),
+        )
+        .procedure(
+            "other",
+            Procedure::builder().query(|_, _: ()| async { Err::<(), _>(Error::Other) }),
+        )
+        .build()
+        .unwrap();
+
+    let code = |name: &str| {
+        let mut stream = procedures
+            .get(name)
+            .unwrap()
+            .exec_with_deserializer((), Value::Null);
+        futures::executor::block_on(stream.next())
+            .unwrap()
+            .unwrap_err()
+            .code()
+    };
+
+    assert_eq!(code("unauthorized"), ErrorCode::Unauthorized);
+    assert_eq!(code("other"), ErrorCode::InternalServerError);
+    assert_eq!(
+        ProcedureError::from(DeserializeError::custom("invalid input")).code(),
+        ErrorCode::BadRequest
+    );
+    assert_eq!(ProcedureError::NotFound.code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn error_code_mappings() {
+    for code in ErrorCode::ALL {
+        assert_eq!(ErrorCode::from_status_code(code.status_code()), Some(code));
+        assert!(code.status_code() >= 400);
+        assert!(code.json_rpc_code() < 0);
+    }
+
+    assert_eq!(ErrorCode::TooManyRequests.status_code(), 429);
+    assert_eq!(ErrorCode::NotFound.json_rpc_code(), -32004);
+    assert_eq!(
+        serde_json::to_value(ErrorCode::NotFound).unwrap(),
+        "NOT_FOUND"
+    );
+    assert_eq!(ErrorCode::from_status_code(200), None);
+}
+
+#[derive(specta::Type)]
+struct Unserializable;
+
+impl serde::Serialize for Unserializable {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("secret internal details"))
+    }
+}
+
+#[test]
+fn serializer_error() {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "unserializable",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>(Unserializable) }),
+        )
+        .build()
+        .unwrap();
+    let procedures = procedures.with_logger({
+        let logs = logs.clone();
+        move |msg| {
+            if let LogMessage::Serializer { procedure, error } = msg {
+                logs.lock()
+                    .unwrap()
+                    .push(format!("{procedure}: {}", error.message()));
+            }
+        }
+    });
+
+    let mut stream = procedures
+        .get("unserializable")
+        .unwrap()
+        .exec_with_deserializer((), Value::Null);
+    let err = futures::executor::block_on(stream.next())
+        .unwrap()
+        .and_then(|v| v.serialize_with(serde_json::value::Serializer))
+        .unwrap_err();
+
+    let ProcedureError::Serializer(inner) = &err else {
+        panic!("expected serializer error but got {err:?}");
+    };
+    assert_eq!(inner.message(), "secret internal details");
+    assert_eq!(err.code(), ErrorCode::InternalServerError);
+    // The details must not be sent to the client
+    assert_eq!(
+        serde_json::to_value(&err).unwrap(),
+        serde_json::json!({ "~rspc": true, "variant": "Serializer", "message": "serializer error" })
+    );
+
+    procedures.logger().log(LogMessage::Serializer {
+        procedure: "unserializable",
+        error: inner,
+    });
+    assert_eq!(
+        *logs.lock().unwrap(),
+        ["unserializable: secret internal details"]
+    );
+}
+
+#[test]
+fn panics_are_caught() {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "query",
+            Procedure::builder().query(|_, _: ()| async {
+                if true {
+                    panic!("query panicked");
+                }
+                Ok::<(), Error>(())
+            }),
+        )
+        .procedure(
+            "subscription",
+            Procedure::builder().subscription(|_, _: ()| async {
+                Ok::<_, Error>(rspc::Stream(futures::stream::iter([1, 2, 3]).map(|v| {
+                    if v == 2 {
+                        panic!("subscription panicked");
+                    }
+                    Ok::<_, Error>(v)
+                })))
+            }),
+        )
+        .build()
+        .unwrap();
+
+    futures::executor::block_on(async {
+        let mut stream = procedures
+            .get("query")
+            .unwrap()
+            .exec_with_deserializer((), Value::Null);
+        let Some(Err(ProcedureError::Unwind(payload))) = stream.next().await else {
+            panic!("expected the query to yield a panic");
+        };
+        assert_eq!(
+            LogMessage::Panic {
+                procedure: "query",
+                payload: payload.as_ref()
+            }
+            .to_string(),
+            "procedure 'query' panicked: query panicked"
+        );
+        assert!(stream.next().await.is_none());
+
+        let mut stream = procedures
+            .get("subscription")
+            .unwrap()
+            .exec_with_deserializer((), Value::Null);
+        assert_eq!(
+            stream
+                .next()
+                .await
+                .unwrap()
+                .unwrap()
+                .serialize_with(serde_json::value::Serializer)
+                .unwrap(),
+            1
+        );
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ProcedureError::Unwind(_)))
+        ));
+        // The stream ends instead of polling the panicked stream again
+        assert!(stream.next().await.is_none());
+    });
+}
+
+#[derive(serde::Deserialize, specta::Type)]
+#[allow(dead_code)]
+struct CreateUser {
+    name: String,
+    emails: Vec<Email>,
+}
+
+#[derive(serde::Deserialize, specta::Type)]
+#[allow(dead_code)]
+struct Email {
+    address: String,
+    primary: bool,
+}
+
+#[test]
+fn deserialize_errors() {
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "create",
+            Procedure::builder().mutation(|_, _: CreateUser| async { Ok::<_, Error>(()) }),
+        )
+        .build()
+        .unwrap();
+
+    let exec = |input: Value| {
+        let mut stream = procedures
+            .get("create")
+            .unwrap()
+            .exec_with_deserializer((), input);
+        match futures::executor::block_on(stream.next()) {
+            Some(Err(ProcedureError::Deserialize(err))) => err,
+            result => panic!("expected deserialize error but got {result:?}"),
+        }
+    };
+
+    let err = exec(serde_json::json!({
+        "name": "Monty",
+        "emails": [
+            { "address": "monty@example.com", "primary": true },
+            { "address": "monty@example.org", "primary": "yes" },
+        ]
+    }));
+    assert_eq!(
+        err.path(),
+        [
+            PathSegment::Field("emails".into()),
+            PathSegment::Index(1),
+            PathSegment::Field("primary".into())
+        ]
+    );
+    assert_eq!(err.expected(), Some("a boolean"));
+    assert_eq!(err.found(), Some("string \"yes\""));
+    assert_eq!(
+        err.to_string(),
+        "emails[1].primary: invalid type: string \"yes\", expected a boolean"
+    );
+    assert_eq!(
+        serde_json::to_value(ProcedureError::Deserialize(err)).unwrap(),
+        serde_json::json!({
+            "~rspc": true,
+            "variant": "Deserialize",
+            "message": "emails[1].primary: invalid type: string \"yes\", expected a boolean",
+            "path": ["emails", 1, "primary"],
+            "expected": "a boolean",
+            "found": "string \"yes\"",
+        })
+    );
+
+    // The path includes the missing field so it can be highlighted
+    let err = exec(
+        serde_json::json!({ "name": "Monty", "emails": [{ "address": "monty@example.com" }] }),
+    );
+    assert_eq!(
+        err.path(),
+        [
+            PathSegment::Field("emails".into()),
+            PathSegment::Index(0),
+            PathSegment::Field("primary".into())
+        ]
+    );
+    assert_eq!(err.found(), None);
+
+    let err = exec(Value::Null);
+    assert!(err.path().is_empty());
+    assert_eq!(err.found(), Some("null"));
+}
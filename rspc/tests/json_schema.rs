@@ -0,0 +1,295 @@
+#![cfg(feature = "json-schema")]
+
+use std::fmt;
+
+use rspc::{JsonSchema, JsonSchemaError, Procedure, ProcedureError, Router, SchemaChangeKind};
+use serde::Serialize;
+use serde_json::json;
+use specta::Type;
+
+#[derive(Serialize, Type)]
+struct User {
+    id: u32,
+    name: Option<String>,
+}
+
+#[test]
+fn json_schema() {
+    let (_, types) = Router::<()>::new()
+        .nest(
+            "users",
+            Router::new().procedure(
+                "get",
+                Procedure::builder()
+                    .query(|_, _: u32| async { Ok::<_, Error>(Vec::<User>::new()) }),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    let schema = JsonSchema::default()
+        .header("")
+        .export_value(&types)
+        .unwrap();
+    assert_eq!(
+        schema,
+        json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "procedures": {
+                "users.get": {
+                    "kind": "query",
+                    "input": { "type": "integer", "format": "u32", "minimum": 0 },
+                    "output": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "id": { "type": "integer", "format": "u32", "minimum": 0 },
+                                "name": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                            },
+                            "required": ["id", "name"],
+                        },
+                    },
+                    "error": { "$ref": "#/$defs/Error" },
+                }
+            },
+            "$defs": {
+                "Error": { "title": "Error", "oneOf": [{ "const": "Internal" }] },
+            },
+        })
+    );
+}
+
+mod v1 {
+    use serde::{Deserialize, Serialize};
+    use specta::Type;
+
+    #[derive(Deserialize, Type)]
+    #[allow(dead_code)]
+    pub struct GetUser {
+        pub id: u32,
+    }
+
+    #[derive(Serialize, Type)]
+    pub struct User {
+        pub id: u32,
+        pub name: String,
+    }
+}
+
+mod v2 {
+    use serde::{Deserialize, Serialize};
+    use specta::Type;
+
+    #[derive(Deserialize, Type)]
+    #[allow(dead_code)]
+    pub struct GetUser {
+        pub id: u32,
+        pub org: u32,
+    }
+
+    #[derive(Serialize, Type)]
+    pub struct User {
+        pub id: u32,
+        pub email: Option<String>,
+    }
+}
+
+#[test]
+fn breaking_changes() {
+    let (_, types) = Router::<()>::new()
+        .procedure(
+            "get",
+            Procedure::builder().query(|_, _: v1::GetUser| async {
+                Ok::<_, Error>(v1::User {
+                    id: 0,
+                    name: String::new(),
+                })
+            }),
+        )
+        .procedure(
+            "delete",
+            Procedure::builder().mutation(|_, _: u32| async { Ok::<_, Error>(()) }),
+        )
+        .procedure(
+            "list",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>(()) }),
+        )
+        .build()
+        .unwrap();
+    let snapshot = JsonSchema::default().export(&types).unwrap();
+
+    assert!(JsonSchema::default()
+        .diff(&snapshot, &types)
+        .unwrap()
+        .changes()
+        .is_empty());
+
+    let (_, types) = Router::<()>::new()
+        .procedure(
+            "get",
+            Procedure::builder().query(|_, _: v2::GetUser| async {
+                Ok::<_, Error>(v2::User { id: 0, email: None })
+            }),
+        )
+        .procedure(
+            "delete",
+            Procedure::builder().query(|_, _: u32| async { Ok::<_, Error>(()) }),
+        )
+        .procedure(
+            "create",
+            Procedure::builder().mutation(|_, _: ()| async { Ok::<_, Error>(()) }),
+        )
+        .build()
+        .unwrap();
+    let diff = JsonSchema::default().diff(&snapshot, &types).unwrap();
+
+    assert!(diff.is_breaking());
+    assert_eq!(
+        diff.changes()
+            .iter()
+            .map(|c| (
+                c.procedure.as_str(),
+                c.location.as_str(),
+                c.kind.clone(),
+                c.is_breaking()
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                "delete",
+                "",
+                SchemaChangeKind::KindChanged {
+                    from: "mutation".into(),
+                    to: "query".into()
+                },
+                true
+            ),
+            (
+                "get",
+                "input.org",
+                SchemaChangeKind::FieldAdded { required: true },
+                true
+            ),
+            (
+                "get",
+                "output.name",
+                SchemaChangeKind::FieldRemoved { required: true },
+                true
+            ),
+            (
+                "get",
+                "output.email",
+                SchemaChangeKind::FieldAdded { required: true },
+                false
+            ),
+            ("list", "", SchemaChangeKind::ProcedureRemoved, true),
+            ("create", "", SchemaChangeKind::ProcedureAdded, false),
+        ]
+    );
+}
+
+mod a {
+    #[derive(serde::Serialize, specta::Type)]
+    pub struct Item {
+        pub a: u32,
+    }
+}
+
+mod b {
+    #[derive(serde::Serialize, specta::Type)]
+    pub struct Item {
+        pub b: u32,
+    }
+}
+
+#[test]
+fn duplicate_type_names() {
+    #[derive(Serialize, Type)]
+    struct Items {
+        a: a::Item,
+        b: b::Item,
+    }
+
+    let (_, types) = Router::<()>::new()
+        .procedure(
+            "get",
+            Procedure::builder().query(|_, _: ()| async {
+                Ok::<_, Error>(Items {
+                    a: a::Item { a: 0 },
+                    b: b::Item { b: 0 },
+                })
+            }),
+        )
+        .build()
+        .unwrap();
+
+    match JsonSchema::default().export_value(&types) {
+        Err(JsonSchemaError::DuplicateTypeName { name, .. }) => assert_eq!(name, "Item"),
+        result => panic!("unexpected result {result:?}"),
+    }
+}
+
+#[test]
+fn recursive_generic_type() {
+    #[derive(Serialize, Type)]
+    struct Tree<T> {
+        value: T,
+        children: Vec<Tree<T>>,
+    }
+
+    #[derive(Serialize, Type)]
+    struct Forest {
+        tree: Tree<u32>,
+    }
+
+    let (_, types) = Router::<()>::new()
+        .procedure(
+            "get",
+            Procedure::builder().query(|_, _: ()| async {
+                Ok::<_, Error>(Forest {
+                    tree: Tree {
+                        value: 0,
+                        children: Vec::new(),
+                    },
+                })
+            }),
+        )
+        .build()
+        .unwrap();
+
+    let schema = JsonSchema::default().export_value(&types).unwrap();
+    let tree = &schema["procedures"]["get"]["output"]["properties"]["tree"];
+    assert_eq!(
+        tree["properties"]["value"],
+        json!({ "type": "integer", "format": "u32", "minimum": 0 })
+    );
+    // The recursion is stopped instead of overflowing the stack.
+    assert_eq!(
+        tree["properties"]["children"],
+        json!({ "type": "array", "items": {} })
+    );
+}
+
+#[derive(Debug, Serialize, Type)]
+#[allow(dead_code)]
+enum Error {
+    Internal,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        ProcedureError::Resolver(rspc_procedure::ResolverError::new(
+            self,
+            None::<std::io::Error>,
+        ))
+    }
+}
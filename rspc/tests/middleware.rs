@@ -0,0 +1,392 @@
+use std::{
+    sync::{Arc, Mutex},
+    task::Poll,
+};
+
+use futures::{stream::BoxStream, StreamExt};
+use rspc::{
+    middleware::{Lifecycle, Middleware, RouterNext, RouterOutput},
+    Procedure, ProcedureError, ProcedureStream, ResolverError, Router,
+};
+use serde_json::{json, Value};
+
+#[derive(Debug, serde::Serialize, specta::Type)]
+pub enum Error {}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        match self {}
+    }
+}
+
+fn procedure() -> Procedure<(), (), &'static str> {
+    Procedure::builder::<Error>().query(|_, _: ()| async { Ok("ok") })
+}
+
+fn exec(router: Router, name: &str) -> Result<Value, ProcedureError> {
+    let (procedures, _) = router.build().unwrap();
+    let mut stream = procedures
+        .get(name)
+        .unwrap()
+        .exec_with_deserializer((), Value::Null);
+
+    futures::executor::block_on(async move {
+        stream
+            .next()
+            .await
+            .unwrap()
+            .map(|v| serde_json::to_value(v.as_serialize().unwrap()).unwrap())
+    })
+}
+
+#[test]
+fn router_middleware() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let layer = |tag: &'static str| {
+        let calls = calls.clone();
+        move |ctx: (), next: RouterNext<()>| {
+            calls
+                .lock()
+                .unwrap()
+                .push(format!("{tag}:{}", next.meta().name()));
+            next.exec(ctx)
+        }
+    };
+
+    let router = <Router>::new()
+        .with(layer("outer"))
+        .procedure("a", procedure())
+        .nest(
+            "admin",
+            <Router>::new()
+                .procedure("b", procedure())
+                .with(layer("admin")),
+        )
+        .merge(<Router>::new().procedure("c", procedure()));
+
+    let (procedures, _) = router.build().unwrap();
+    futures::executor::block_on(async {
+        for name in ["a", "admin.b", "c"] {
+            let mut stream = procedures
+                .get(name)
+                .unwrap()
+                .exec_with_deserializer((), Value::Null);
+            let value = stream.next().await.unwrap().unwrap();
+            assert_eq!(
+                serde_json::to_value(value.as_serialize().unwrap()).unwrap(),
+                json!("ok")
+            );
+        }
+    });
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["outer:a", "outer:admin.b", "admin:admin.b", "outer:c"]
+    );
+}
+
+#[test]
+fn router_middleware_short_circuit() {
+    let router = <Router>::new().procedure("a", procedure());
+    let router = router.with(|_: (), _: RouterNext<()>| async {
+        Err(ResolverError::new("unauthorized", None::<std::io::Error>).into())
+    });
+
+    match exec(router, "a") {
+        Err(ProcedureError::Resolver(err)) => assert_eq!(
+            serde_json::to_value(err.value()).unwrap(),
+            json!("unauthorized")
+        ),
+        result => panic!("unexpected result {result:?}"),
+    }
+
+    let router = <Router>::new()
+        .procedure("a", procedure())
+        .with(|ctx: (), next: RouterNext<()>| next.exec(ctx));
+    assert_eq!(exec(router, "a").unwrap(), json!("ok"));
+}
+
+#[test]
+#[cfg(feature = "legacy")]
+fn router_middleware_legacy() {
+    fn legacy_router() -> Router {
+        let legacy = rspc_legacy::Router::<()>::new()
+            .query("a", |t| t(|_, _: ()| "ok"))
+            .build();
+        <Router>::new().nest("legacy", legacy.into())
+    }
+
+    let router = legacy_router().with(|_: (), _: RouterNext<()>| async {
+        Err(ResolverError::new("unauthorized", None::<std::io::Error>).into())
+    });
+    match exec(router, "legacy.a") {
+        Err(ProcedureError::Resolver(err)) => assert_eq!(
+            serde_json::to_value(err.value()).unwrap(),
+            json!("unauthorized")
+        ),
+        result => panic!("unexpected result {result:?}"),
+    }
+
+    let router = legacy_router().with(|ctx: (), next: RouterNext<()>| next.exec(ctx));
+    assert_eq!(exec(router, "legacy.a").unwrap(), json!("ok"));
+}
+
+#[test]
+fn procedures_layer() {
+    let (procedures, _) = <Router>::new()
+        .procedure("a", procedure())
+        .procedure("b", procedure())
+        .build()
+        .unwrap();
+
+    let procedures = procedures.layer(
+        |name: &str, procedure: &rspc_procedure::Procedure<()>, ctx, input: rspc::DynInput| {
+            assert_eq!(procedure.kind(), rspc_procedure::ProcedureKind::Query);
+            if name == "b" {
+                return ProcedureError::NotFound.into();
+            }
+            procedure.exec(ctx, input)
+        },
+    );
+
+    futures::executor::block_on(async {
+        let mut stream = procedures["a"].exec_with_deserializer((), Value::Null);
+        let value = stream.next().await.unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(value.as_serialize().unwrap()).unwrap(),
+            json!("ok")
+        );
+        assert!(stream.next().await.is_none());
+
+        let mut stream = procedures["b"].exec_with_deserializer((), Value::Null);
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ProcedureError::NotFound))
+        ));
+        assert!(stream.next().await.is_none());
+    });
+}
+
+#[test]
+fn procedure_alias() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let router = {
+        let calls = calls.clone();
+        <Router>::new()
+            .nest("users", <Router>::new().procedure("get", procedure()))
+            .alias("getUser", "users.get")
+            .with(move |ctx: (), next: RouterNext<()>| {
+                calls.lock().unwrap().push((
+                    next.meta().name().to_string(),
+                    next.meta().alias_of().map(ToString::to_string),
+                ));
+                next.exec(ctx)
+            })
+    };
+
+    let (procedures, types) = router.build().unwrap();
+    for name in ["users.get", "getUser"] {
+        let mut stream = procedures[name].exec_with_deserializer((), Value::Null);
+        let value = futures::executor::block_on(stream.next()).unwrap().unwrap();
+        assert_eq!(
+            serde_json::to_value(value.as_serialize().unwrap()).unwrap(),
+            json!("ok")
+        );
+    }
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            ("users.get".to_string(), None),
+            ("getUser".to_string(), Some("users.get".to_string()))
+        ]
+    );
+
+    let types = types.procedures().collect::<Vec<_>>();
+    assert_eq!(types[0].0, vec!["getUser"]);
+    assert!(matches!(
+        types[0].1.deprecated(),
+        Some(specta::datatype::DeprecatedType::DeprecatedWithSince { note, .. }) if note == "Use `users.get` instead"
+    ));
+    assert_eq!(types[1].0, vec!["users", "get"]);
+    assert_eq!(types[1].1.deprecated(), None);
+}
+
+#[test]
+fn subscription_lifecycle() {
+    let log = Arc::new(Mutex::new(Vec::<String>::new()));
+    let push = |log: &Arc<Mutex<Vec<String>>>, v: String| log.lock().unwrap().push(v);
+
+    let subscription = |log: Arc<Mutex<Vec<String>>>| {
+        Procedure::builder::<Error>()
+            .with(Middleware::new(move |ctx: (), input: bool, next| {
+                let log = log.clone();
+                async move {
+                    let stream: rspc::Stream<BoxStream<'static, Result<u32, Error>>> =
+                        next.exec(ctx, input).await?;
+                    let (log2, log3) = (log.clone(), log.clone());
+                    Ok(rspc::Stream(
+                        Lifecycle::new(stream.0)
+                            .on_start(move || async move { push(&log, "start".into()) })
+                            .on_item(|v| v.map(|v| v * 10))
+                            .on_end(move |reason| async move {
+                                push(&log2, format!("end:{reason:?}"));
+                                // Ensure async hooks are driven to completion.
+                                let mut yielded = false;
+                                std::future::poll_fn(|cx| {
+                                    if yielded {
+                                        return Poll::Ready(());
+                                    }
+                                    yielded = true;
+                                    cx.waker().wake_by_ref();
+                                    Poll::Pending
+                                })
+                                .await;
+                            })
+                            .on_unsubscribe(
+                                move || async move { push(&log3, "unsubscribe".into()) },
+                            ),
+                    ))
+                }
+            }))
+            .subscription(|_, infinite: bool| async move {
+                let stream = futures::stream::iter((1..3).map(Ok::<_, Error>));
+                Ok(match infinite {
+                    true => stream.chain(futures::stream::pending()).boxed(),
+                    false => stream.boxed(),
+                })
+            })
+    };
+
+    let (procedures, _) = <Router>::new()
+        .procedure("sub", subscription(log.clone()))
+        .build()
+        .unwrap();
+    let exec = |infinite: bool| {
+        procedures
+            .get("sub")
+            .unwrap()
+            .exec_with_deserializer((), Value::Bool(infinite))
+    };
+    let next = |stream: &mut ProcedureStream| {
+        futures::executor::block_on(stream.next())
+            .map(|v| serde_json::to_value(v.unwrap().as_serialize().unwrap()).unwrap())
+    };
+    let take_log = || std::mem::take(&mut *log.lock().unwrap());
+
+    // The stream completes
+    let mut stream = exec(false);
+    assert_eq!(next(&mut stream), Some(json!(10)));
+    assert_eq!(next(&mut stream), Some(json!(20)));
+    assert_eq!(next(&mut stream), None);
+    assert_eq!(take_log(), vec!["start", "end:Complete"]);
+
+    // The client unsubscribes
+    let mut stream = exec(true);
+    assert_eq!(next(&mut stream), Some(json!(10)));
+    stream.close();
+    assert_eq!(next(&mut stream), None);
+    assert_eq!(take_log(), vec!["start", "end:Unsubscribed", "unsubscribe"]);
+
+    // The stream is dropped without being closed
+    let mut stream = exec(true);
+    assert_eq!(next(&mut stream), Some(json!(10)));
+    drop(stream);
+    assert_eq!(take_log(), vec!["start", "end:Dropped"]);
+}
+
+#[test]
+fn stream_middleware() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let (procedures, _) = <Router>::new()
+        .procedure("sub", {
+            let seen = seen.clone();
+            Procedure::builder::<Error>()
+                .with(Middleware::stream(move |_: &(), meta| {
+                    let seen = seen.clone();
+                    move |item: Result<u32, Error>| {
+                        seen.lock()
+                            .unwrap()
+                            .push(format!("{}:{item:?}", meta.name()));
+                        async move {
+                            match item {
+                                Ok(v) if v % 2 == 0 => None,
+                                item => Some(item.map(|v| v * 10)),
+                            }
+                        }
+                    }
+                }))
+                .subscription(|_, _: ()| async move {
+                    Ok(futures::stream::iter((1..5).map(Ok::<_, Error>)))
+                })
+        })
+        .build()
+        .unwrap();
+
+    let mut stream = procedures
+        .get("sub")
+        .unwrap()
+        .exec_with_deserializer((), Value::Null);
+    let result = futures::executor::block_on(async {
+        let mut result = Vec::new();
+        while let Some(v) = stream.next().await {
+            result.push(serde_json::to_value(v.unwrap().as_serialize().unwrap()).unwrap());
+        }
+        result
+    });
+
+    assert_eq!(result, vec![json!(10), json!(30)]);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec!["sub:Ok(1)", "sub:Ok(2)", "sub:Ok(3)", "sub:Ok(4)"]
+    );
+}
+
+#[test]
+fn router_middleware_foreign_output() {
+    // The middleware holds onto the output of `other` and returns it from the next procedure instead.
+    let stash = Arc::new(Mutex::new(None::<RouterOutput>));
+    let router = <Router>::new().procedure("a", procedure()).procedure(
+        "other",
+        Procedure::builder::<Error>().query(|_, _: ()| async { Ok(42) }),
+    );
+    #[cfg(feature = "legacy")]
+    let router = router.nest(
+        "legacy",
+        rspc_legacy::Router::<()>::new()
+            .query("a", |t| t(|_, _: ()| "ok"))
+            .build()
+            .into(),
+    );
+    let router = router.with(move |ctx: (), next: RouterNext<()>| {
+        let stash = stash.clone();
+        async move {
+            if next.meta().name() != "other" {
+                return Ok(stash.lock().unwrap().take().unwrap());
+            }
+
+            *stash.lock().unwrap() = Some(next.exec(ctx).await?);
+            Err(ResolverError::new("stashed", None::<std::io::Error>).into())
+        }
+    });
+
+    let (procedures, _) = router.build().unwrap();
+    let exec = |name: &str| {
+        let mut stream = procedures
+            .get(name)
+            .unwrap()
+            .exec_with_deserializer((), Value::Null);
+        futures::executor::block_on(async move { stream.next().await.unwrap().map(|_| ()) })
+    };
+
+    let mut names = vec!["a"];
+    #[cfg(feature = "legacy")]
+    names.push("legacy.a");
+    for name in names {
+        assert!(matches!(exec("other"), Err(ProcedureError::Resolver(_))));
+        match exec(name) {
+            Err(ProcedureError::Downcast(_)) => {}
+            result => panic!("unexpected result for {name:?}: {result:?}"),
+        }
+    }
+}
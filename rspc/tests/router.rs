@@ -1,6 +1,6 @@
 use std::fmt;
 
-use rspc::{Procedure, ProcedureError, Router};
+use rspc::{Procedure, ProcedureError, Router, State};
 use rspc_procedure::ResolverError;
 use serde::Serialize;
 use specta::Type;
@@ -53,6 +53,191 @@ fn errors() {
     assert_eq!(format!("{:?}", router.build().unwrap_err()), "[Duplicate procedure at path [\"abc\"]. Original: rspc/tests/router.rs:42:17 Duplicate: rspc/tests/router.rs:45:10\n]");
 }
 
+#[test]
+fn alias_errors() {
+    let router = <Router>::new()
+        .procedure(
+            "abc",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+        )
+        .alias("def", "ghi");
+    assert_eq!(
+        format!("{:?}", router.build().unwrap_err()),
+        "[Alias [\"def\"] points to unknown procedure [\"ghi\"]. Alias: rspc/tests/router.rs:63:10\n]"
+    );
+
+    let router = <Router>::new()
+        .procedure(
+            "abc",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+        )
+        .alias("abc", "abc");
+    assert_eq!(
+        format!("{:?}", router.build().unwrap_err()),
+        "[Duplicate procedure at path [\"abc\"]. Original: rspc/tests/router.rs:72:13 Duplicate: rspc/tests/router.rs:74:10\n]"
+    );
+}
+
+#[test]
+fn procedure_names() {
+    #[derive(Default)]
+    struct Names(Vec<String>);
+
+    fn procedure() -> Procedure<(), (), ()> {
+        Procedure::builder()
+            .setup(|state: &mut State, meta| {
+                state
+                    .get_mut_or_init(Names::default)
+                    .0
+                    .push(meta.name().to_string())
+            })
+            .query(|_, _: ()| async { Ok::<_, Infallible>(()) })
+    }
+
+    let (procedures, _) = <Router>::new()
+        .procedure("a", procedure())
+        .nest(
+            "b",
+            <Router>::new()
+                .procedure("c", procedure())
+                .nest("d", <Router>::new().procedure("e", procedure())),
+        )
+        .merge(<Router>::new().procedure("f", procedure()))
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        procedures.state().get::<Names>().unwrap().0,
+        vec!["a", "b.c", "b.d.e", "f"]
+    );
+}
+
+#[test]
+fn required_state() {
+    struct Config;
+
+    fn router() -> Router {
+        <Router>::new().procedure(
+            "a",
+            Procedure::builder()
+                .setup(|state: &mut State, _| state.require::<Config>())
+                .query(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+        )
+    }
+
+    assert_eq!(
+        format!("{:?}", router().build().unwrap_err()),
+        "[Missing required state 'router::required_state::Config'. It must be provided with `Router::setup` or `Router::build_with_state`.\n]"
+    );
+
+    let mut state = State::default();
+    state.insert(Config);
+    assert!(router().build_with_state(state).is_ok());
+
+    assert!(router().setup(|state| state.insert(Config)).build().is_ok());
+}
+
+#[test]
+fn procedure_kind_and_metadata() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct RateLimit(u32);
+
+    let (procedures, _) = <Router>::new()
+        .procedure(
+            "a",
+            Procedure::builder()
+                .query(|_, _: ()| async { Ok::<_, Infallible>(()) })
+                .meta(RateLimit(5)),
+        )
+        .procedure(
+            "b",
+            Procedure::builder().mutation(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+        )
+        .build()
+        .unwrap();
+
+    assert_eq!(procedures["a"].kind(), rspc_procedure::ProcedureKind::Query);
+    assert_eq!(procedures["a"].metadata::<RateLimit>(), Some(&RateLimit(5)));
+    assert_eq!(
+        procedures["b"].kind(),
+        rspc_procedure::ProcedureKind::Mutation
+    );
+    assert_eq!(procedures["b"].metadata::<RateLimit>(), None);
+}
+
+#[test]
+fn types_introspection() {
+    // The metadata doesn't need to be `Clone` even though it's shared with the built procedure.
+    #[derive(Debug, PartialEq)]
+    struct RateLimit(u32);
+
+    let (_, types) = Router::<()>::new()
+        .procedure(
+            "a",
+            Procedure::builder()
+                .query(|_, _: i32| async { Ok::<_, Infallible>(String::new()) })
+                .meta(RateLimit(5)),
+        )
+        .nest(
+            "nested",
+            Router::new().procedure(
+                "b",
+                Procedure::builder().mutation(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+            ),
+        )
+        .build()
+        .unwrap();
+
+    let procedures = types.procedures().collect::<Vec<_>>();
+    assert_eq!(
+        procedures
+            .iter()
+            .map(|(path, ty)| (path.join("."), ty.kind()))
+            .collect::<Vec<_>>(),
+        vec![
+            ("a".to_string(), rspc::ProcedureKind::Query),
+            ("nested.b".to_string(), rspc::ProcedureKind::Mutation),
+        ]
+    );
+
+    let (_, a) = &procedures[0];
+    assert_eq!(a.metadata::<RateLimit>(), Some(&RateLimit(5)));
+    assert!(matches!(
+        a.input(),
+        specta::datatype::DataType::Primitive(_)
+    ));
+    assert_eq!(a.location().file(), file!());
+
+    let (_, b) = &procedures[1];
+    assert_eq!(b.metadata::<RateLimit>(), None);
+}
+
+#[test]
+fn merge_alias_errors() {
+    let router = <Router>::new()
+        .procedure(
+            "abc",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+        )
+        .alias("def", "abc")
+        .merge(<Router>::new().alias("def", "ghi"));
+    assert_eq!(
+        format!("{:?}", router.build().unwrap_err()),
+        "[Duplicate procedure at path [\"def\"]. Original: rspc/tests/router.rs:222:10 Duplicate: rspc/tests/router.rs:223:32\n]"
+    );
+
+    let router = <Router>::new()
+        .procedure(
+            "abc",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Infallible>(()) }),
+        )
+        .merge(<Router>::new().alias("abc", "abc"));
+    assert_eq!(
+        format!("{:?}", router.build().unwrap_err()),
+        "[Duplicate procedure at path [\"abc\"]. Original: rspc/tests/router.rs:232:13 Duplicate: rspc/tests/router.rs:234:32\n]"
+    );
+}
+
 #[derive(Type, Debug)]
 pub enum Infallible {}
 
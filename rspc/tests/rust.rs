@@ -0,0 +1,118 @@
+#![cfg(feature = "rust")]
+
+use std::{collections::HashMap, fmt};
+
+use rspc::{ProcedureError, Router, Rust};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+// The output of `rspc::Rust` is committed so we can ensure it compiles.
+#[allow(clippy::all)]
+#[rustfmt::skip]
+#[path = "rust/bindings.rs"]
+mod bindings;
+
+#[derive(Deserialize, Type)]
+#[allow(dead_code)]
+pub struct GetUser {
+    id: u32,
+}
+
+/// A user of the application
+#[derive(Serialize, Type)]
+pub struct User {
+    id: u32,
+    #[serde(rename = "type")]
+    ty: Kind,
+    tags: HashMap<String, Vec<String>>,
+    rating: f32,
+}
+
+#[derive(Serialize, Type)]
+#[serde(tag = "kind")]
+pub enum Kind {
+    Admin,
+    Member { since: Option<String> },
+}
+
+#[derive(Debug, Serialize, Type)]
+#[allow(dead_code)]
+enum Error {
+    NotFound(String),
+}
+
+fn router() -> Router<()> {
+    Router::new()
+        .procedure(
+            "version",
+            rspc::Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>(String::new()) }),
+        )
+        .nest(
+            "users",
+            Router::new()
+                .procedure(
+                    "get",
+                    rspc::Procedure::builder()
+                        .description("Get a user by their id")
+                        .query(|_, _: GetUser| async {
+                            Ok::<_, Error>(User {
+                                id: 0,
+                                ty: Kind::Admin,
+                                tags: Default::default(),
+                                rating: 0.0,
+                            })
+                        }),
+                )
+                .procedure(
+                    "delete",
+                    rspc::Procedure::builder()
+                        .mutation(|_, _: (u32, bool)| async { Ok::<_, Error>(()) }),
+                ),
+        )
+}
+
+#[test]
+fn rust_bindings() {
+    let (_, types) = router().build().unwrap();
+    let bindings = Rust::default().export(&types);
+
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/rust/bindings.rs");
+    if std::env::var("UPDATE_BINDINGS").is_ok() {
+        std::fs::write(path, &bindings).unwrap();
+    }
+    assert_eq!(
+        bindings,
+        std::fs::read_to_string(path).unwrap(),
+        "bindings are out of date. Run with `UPDATE_BINDINGS=1` to update them."
+    );
+
+    fn procedure<P: rspc_client::Procedure<Procedures = bindings::Procedures>>(
+    ) -> (&'static str, rspc_client::ProcedureKind) {
+        (P::KEY, P::KIND)
+    }
+    assert_eq!(
+        procedure::<bindings::users::get>(),
+        ("users.get", rspc_client::ProcedureKind::Query)
+    );
+    assert_eq!(
+        procedure::<bindings::users::delete>(),
+        ("users.delete", rspc_client::ProcedureKind::Mutation)
+    );
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> ProcedureError {
+        ProcedureError::Resolver(rspc_procedure::ResolverError::new(
+            self,
+            None::<std::io::Error>,
+        ))
+    }
+}
@@ -0,0 +1,74 @@
+//! This file was generated by [rspc](https://github.com/specta-rs/rspc). Do not edit this file manually.
+
+#![allow(non_camel_case_types, non_snake_case, dead_code, unused_imports, deprecated)]
+
+pub struct Procedures;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, rspc_client::internal::serde::Serialize, rspc_client::internal::serde::Deserialize)]
+#[serde(crate = "rspc_client::internal::serde")]
+pub enum Error {
+    NotFound(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, rspc_client::internal::serde::Serialize, rspc_client::internal::serde::Deserialize)]
+#[serde(crate = "rspc_client::internal::serde")]
+pub struct GetUser {
+    pub id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, rspc_client::internal::serde::Serialize, rspc_client::internal::serde::Deserialize)]
+#[serde(crate = "rspc_client::internal::serde")]
+#[serde(tag = "kind")]
+pub enum Kind {
+    Admin,
+    Member {
+        since: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, rspc_client::internal::serde::Serialize, rspc_client::internal::serde::Deserialize)]
+#[serde(crate = "rspc_client::internal::serde")]
+pub struct User {
+    pub id: u32,
+    pub r#type: Kind,
+    pub tags: std::collections::HashMap<String, Vec<String>>,
+    pub rating: f32,
+}
+
+pub mod users {
+    use super::*;
+
+    pub struct delete;
+
+    impl rspc_client::Procedure for delete {
+        type Input = (u32, bool);
+        type Output = ();
+        type Error = Error;
+        type Procedures = Procedures;
+        const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Mutation;
+        const KEY: &'static str = "users.delete";
+    }
+
+    /// Get a user by their id
+    pub struct get;
+
+    impl rspc_client::Procedure for get {
+        type Input = GetUser;
+        type Output = User;
+        type Error = Error;
+        type Procedures = Procedures;
+        const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
+        const KEY: &'static str = "users.get";
+    }
+}
+
+pub struct version;
+
+impl rspc_client::Procedure for version {
+    type Input = ();
+    type Output = String;
+    type Error = Error;
+    type Procedures = Procedures;
+    const KIND: rspc_client::ProcedureKind = rspc_client::ProcedureKind::Query;
+    const KEY: &'static str = "version";
+}
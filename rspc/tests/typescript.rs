@@ -105,3 +105,177 @@
 //     export_rspc_types();
 //     tsc("examples/astro/test/solid.test.tsx", JSXMode::Solid);
 // }
+
+#[cfg(feature = "typescript")]
+#[derive(Debug, specta::Type, serde::Serialize)]
+enum Error {}
+
+#[cfg(feature = "typescript")]
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "typescript")]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "typescript")]
+impl rspc::Error for Error {
+    fn into_procedure_error(self) -> rspc::ProcedureError {
+        match self {}
+    }
+}
+
+#[cfg(all(feature = "typescript", feature = "legacy"))]
+#[test]
+fn source_maps() {
+    use std::path::Path;
+
+    use rspc::{Procedure, Router, Typescript};
+
+    #[derive(specta::Type, serde::Serialize)]
+    struct User {
+        id: u32,
+    }
+
+    // The location of a procedure is where it's builder was created.
+    let procedure_line = line!() + 4;
+    let router = Router::<()>::new()
+        .procedure(
+            "user",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>(User { id: 0 }) }),
+        )
+        .nest(
+            "nested",
+            Router::new().procedure(
+                "get",
+                Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>(()) }),
+            ),
+        )
+        .merge(
+            rspc_legacy::Router::<()>::new()
+                .query("legacy", |t| t(|_, _: ()| "ok"))
+                .build()
+                .into(),
+        );
+    let nest_line = procedure_line + 2;
+    let nested_procedure_line = nest_line + 4;
+    // The location of a legacy procedure is where it was added to the legacy router.
+    let legacy_procedure_line = nested_procedure_line + 5;
+
+    let dir = std::env::temp_dir().join("rspc-source-maps");
+    let (_, types) = router.build().unwrap();
+    Typescript::default()
+        .enable_source_maps()
+        .export_to(dir.join("bindings.ts"), &types)
+        .unwrap();
+
+    let bindings = std::fs::read_to_string(dir.join("bindings.ts")).unwrap();
+    assert!(bindings.contains("export type { Procedures } from './bindings_t';"));
+
+    let d_ts = std::fs::read_to_string(dir.join("bindings_t.d.ts")).unwrap();
+    assert!(d_ts.contains("import type { Error } from './bindings';"));
+    assert!(d_ts.ends_with("//# sourceMappingURL=bindings_t.d.ts.map\n"));
+
+    let map: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("bindings_t.d.ts.map")).unwrap())
+            .unwrap();
+    assert_eq!(map["file"], "bindings_t.d.ts");
+    assert_eq!(
+        map["names"],
+        serde_json::json!(["legacy", "nested", "get", "user"])
+    );
+
+    // The source must resolve relative to the source map
+    let source = map["sources"][0].as_str().unwrap();
+    assert_eq!(
+        dir.join(source).canonicalize().unwrap(),
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/typescript.rs")
+            .canonicalize()
+            .unwrap()
+    );
+
+    // Decode the mappings and check each key points at the correct line.
+    let mut lines = Vec::new();
+    let (mut source_line, mut name) = (0, 0);
+    for (generated_line, segments) in map["mappings"].as_str().unwrap().split(';').enumerate() {
+        for segment in segments.split(',').filter(|s| !s.is_empty()) {
+            let values = decode_vlq(segment);
+            source_line += values[2];
+            name += values[4];
+            lines.push((
+                map["names"][name as usize].as_str().unwrap().to_string(),
+                d_ts.lines().nth(generated_line).unwrap().trim().to_string(),
+                source_line as u32 + 1,
+            ));
+        }
+    }
+    assert_eq!(
+        lines
+            .iter()
+            .map(|(name, line, source_line)| (
+                name.as_str(),
+                line.starts_with(&format!("{name}: ")),
+                *source_line
+            ))
+            .collect::<Vec<_>>(),
+        vec![
+            ("legacy", true, legacy_procedure_line),
+            ("nested", true, nest_line),
+            ("get", true, nested_procedure_line),
+            ("user", true, procedure_line),
+        ]
+    );
+}
+
+#[cfg(feature = "typescript")]
+#[test]
+fn procedure_docs() {
+    use rspc::{Procedure, Router, Typescript};
+
+    let router = Router::<()>::new()
+        .procedure(
+            "plain",
+            Procedure::builder().query(|_, _: ()| async { Ok::<_, Error>(()) }),
+        )
+        .procedure(
+            "user",
+            Procedure::builder()
+                .description("Get the current user.\nReturns `null` if not authenticated.")
+                .deprecated("Use `users.get` instead")
+                .query(|_, _: ()| async { Ok::<_, Error>(()) }),
+        );
+
+    let path = std::env::temp_dir().join("rspc-procedure-docs/bindings.ts");
+    let (_, types) = router.build().unwrap();
+    Typescript::default().export_to(&path, &types).unwrap();
+
+    let bindings = std::fs::read_to_string(&path).unwrap();
+    assert!(bindings.contains(
+        "\tplain: { kind: \"query\", input: null, output: null, error: Error },\n\t/**\n\t * Get the current user.\n\t * Returns `null` if not authenticated.\n\t * @deprecated Use `users.get` instead\n\t */\n\tuser: {"
+    ), "{bindings}");
+}
+
+#[cfg(feature = "typescript")]
+fn decode_vlq(segment: &str) -> Vec<i64> {
+    const B64_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut values = Vec::new();
+    let (mut value, mut shift) = (0, 0);
+    for c in segment.bytes() {
+        let digit = B64_CHARS.iter().position(|b| *b == c).unwrap() as i64;
+        value += (digit & 0b11111) << shift;
+        shift += 5;
+        if digit & 0b100000 == 0 {
+            values.push(if value & 1 == 1 {
+                -(value >> 1)
+            } else {
+                value >> 1
+            });
+            (value, shift) = (0, 0);
+        }
+    }
+    values
+}